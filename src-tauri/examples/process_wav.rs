@@ -0,0 +1,185 @@
+//! Runs the native microphone processing chain over a WAV file, so gate and denoise changes can
+//! be compared reproducibly without a microphone.
+//!
+//! ```
+//! $ cargo run --example process_wav -- input.wav output.wav \
+//!     --settings '{"noiseSuppressionMode":"rnnoise","inputSensitivity":0.06,"gateMode":"vad"}'
+//! ```
+//!
+//! The input must be 48 kHz; multi-channel input is averaged down to mono. `output.wav` holds
+//! what would have been published, gate attenuation included, and a CSV with one line
+//! of gate/VAD decisions per 10 ms frame is written next to it unless `--decisions` says where.
+
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use chitchat_temp_lib::audio_backend::{read_wav_interleaved, StreamFormat};
+use chitchat_temp_lib::voice_processing::{
+    VoiceProcessingSettings, VoiceProcessor, SAMPLES_PER_FRAME, SAMPLE_RATE,
+};
+
+struct Args {
+    input: PathBuf,
+    output: PathBuf,
+    decisions: PathBuf,
+    settings: VoiceProcessingSettings,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let usage =
+        "usage: process_wav <input.wav> <output.wav> [--settings <json>] [--decisions <file.csv>]";
+    let mut positional = Vec::new();
+    let mut settings = VoiceProcessingSettings::default();
+    let mut decisions = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--settings" => {
+                let json = args.next().ok_or(usage)?;
+                settings = serde_json::from_str(&json).map_err(|err| err.to_string())?;
+            }
+            "--decisions" => decisions = Some(PathBuf::from(args.next().ok_or(usage)?)),
+            _ => positional.push(PathBuf::from(arg)),
+        }
+    }
+    let [input, output] = <[PathBuf; 2]>::try_from(positional).map_err(|_| usage.to_string())?;
+    let decisions = decisions.unwrap_or_else(|| output.with_extension("csv"));
+    Ok(Args {
+        input,
+        output,
+        decisions,
+        settings,
+    })
+}
+
+/// Frame counts reported at the end of a run.
+struct Summary {
+    frames: usize,
+    open_frames: usize,
+}
+
+fn process(args: &Args) -> Result<Summary, String> {
+    let input = read_wav_interleaved(&args.input, StreamFormat::new(SAMPLE_RATE, 1))?;
+    let mut processor = VoiceProcessor::new(&args.settings)?;
+
+    let mut writer = hound::WavWriter::create(
+        &args.output,
+        hound::WavSpec {
+            channels: 1,
+            sample_rate: SAMPLE_RATE,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        },
+    )
+    .map_err(|err| err.to_string())?;
+    let mut decisions =
+        BufWriter::new(File::create(&args.decisions).map_err(|err| err.to_string())?);
+    writeln!(
+        decisions,
+        "frame,time_ms,rms,peak,voice_probability,gate_open,gate_gain"
+    )
+    .map_err(|err| err.to_string())?;
+
+    let mut frame = vec![0.0f32; SAMPLES_PER_FRAME];
+    let mut open_frames = 0usize;
+    let total_frames = input.len().div_ceil(SAMPLES_PER_FRAME);
+    for (index, chunk) in input.chunks(SAMPLES_PER_FRAME).enumerate() {
+        frame.fill(0.0);
+        frame[..chunk.len()].copy_from_slice(chunk);
+        let decision = processor.process_frame(&mut frame);
+        if decision.gate_open {
+            open_frames += 1;
+        }
+        writeln!(
+            decisions,
+            "{},{},{:.6},{:.6},{:.4},{},{:.4}",
+            index,
+            index * 10,
+            decision.rms,
+            decision.peak,
+            decision.voice_probability,
+            u8::from(decision.gate_open),
+            decision.gate_gain
+        )
+        .map_err(|err| err.to_string())?;
+        for sample in &frame[..chunk.len()] {
+            writer
+                .write_sample((sample.clamp(-1.0, 1.0) * 32767.0) as i16)
+                .map_err(|err| err.to_string())?;
+        }
+    }
+    writer.finalize().map_err(|err| err.to_string())?;
+    decisions.flush().map_err(|err| err.to_string())?;
+
+    Ok(Summary {
+        frames: total_frames,
+        open_frames,
+    })
+}
+
+fn run() -> Result<(), String> {
+    let args = parse_args()?;
+    let summary = process(&args)?;
+    println!(
+        "{} frames, gate open for {} ({:.1}%)",
+        summary.frames,
+        summary.open_frames,
+        summary.open_frames as f32 * 100.0 / summary.frames.max(1) as f32
+    );
+    Ok(())
+}
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("{err}");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    fn rms(samples: &[f32]) -> f32 {
+        (samples.iter().map(|sample| sample * sample).sum::<f32>() / samples.len() as f32).sqrt()
+    }
+
+    #[test]
+    fn processes_the_tone_fixture() {
+        // 300 ms of a 440 Hz tone at -12 dBFS followed by 300 ms of silence.
+        let input =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/fixtures/tone_then_silence.wav");
+        let output =
+            std::env::temp_dir().join(format!("chitchat-{}-processed.wav", std::process::id()));
+        let args = Args {
+            input,
+            decisions: output.with_extension("csv"),
+            output,
+            settings: serde_json::from_str(
+                r#"{"noiseSuppressionMode":"off","inputSensitivity":0.06}"#,
+            )
+            .unwrap(),
+        };
+
+        let summary = process(&args).unwrap();
+        let processed = read_wav_interleaved(&args.output, StreamFormat::new(SAMPLE_RATE, 1));
+        let _ = std::fs::remove_file(&args.output);
+        let _ = std::fs::remove_file(&args.decisions);
+        let processed = processed.unwrap();
+
+        assert_eq!(summary.frames, 60);
+        assert_eq!(processed.len(), 60 * SAMPLES_PER_FRAME);
+        // Open for the 30 tone frames; the default 180 ms hold closes it on the 18th silent one.
+        assert_eq!(summary.open_frames, 30 + 17);
+        let (tone, silence) = processed.split_at(processed.len() / 2);
+        // The gate's release reaches a little way into the silence, so skip that part.
+        let silence = &silence[silence.len() / 2..];
+        assert!(rms(tone) > 0.05, "tone rms {}", rms(tone));
+        assert!(rms(silence) < 0.001, "silence rms {}", rms(silence));
+    }
+}
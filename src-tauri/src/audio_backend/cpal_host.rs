@@ -0,0 +1,327 @@
+use std::collections::VecDeque;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc, Arc, Mutex,
+};
+use std::time::Duration;
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, Sample, SampleFormat, SizedSample};
+
+use super::{
+    AudioDeviceInfo, CaptureBackend, CaptureStream, LoopbackTarget, PlaybackBackend,
+    PlaybackStream, StreamFormat,
+};
+
+/// ALSA on Linux (which also fronts PulseAudio and PipeWire through their ALSA plugins) and
+/// CoreAudio on macOS. Loopback capture on Linux goes to the sound server directly, since its
+/// monitor sources are not reachable through ALSA.
+pub(super) struct CpalBackend;
+
+fn find_input_device(host: &cpal::Host, device_id: Option<&str>) -> Option<cpal::Device> {
+    let device_id = device_id.filter(|id| !id.is_empty())?;
+    host.input_devices()
+        .ok()?
+        .find(|device| device.name().map(|name| name == device_id).unwrap_or(false))
+}
+
+fn find_output_device(host: &cpal::Host, device_id: Option<&str>) -> Option<cpal::Device> {
+    let device_id = device_id.filter(|id| !id.is_empty())?;
+    host.output_devices()
+        .ok()?
+        .find(|device| device.name().map(|name| name == device_id).unwrap_or(false))
+}
+
+impl CaptureBackend for CpalBackend {
+    fn list_input_devices(&self) -> Result<Vec<AudioDeviceInfo>, String> {
+        let host = cpal::default_host();
+        let devices = host.input_devices().map_err(|err| err.to_string())?;
+        Ok(devices
+            .filter_map(|device| device.name().ok())
+            .map(|name| AudioDeviceInfo {
+                id: name.clone(),
+                label: name,
+            })
+            .collect())
+    }
+
+    fn default_input_device_id(&self) -> Option<String> {
+        cpal::default_host().default_input_device()?.name().ok()
+    }
+
+    fn input_format(&self, device_id: Option<&str>) -> Result<StreamFormat, String> {
+        let host = cpal::default_host();
+        let device = find_input_device(&host, device_id)
+            .or_else(|| host.default_input_device())
+            .ok_or_else(|| "No audio input device is available".to_string())?;
+        let config = device
+            .default_input_config()
+            .map_err(|err| err.to_string())?;
+        Ok(StreamFormat::new(config.sample_rate().0, config.channels()))
+    }
+
+    fn open_input(
+        &self,
+        device_id: Option<&str>,
+        format: StreamFormat,
+    ) -> Result<Box<dyn CaptureStream>, String> {
+        let host = cpal::default_host();
+        let device = find_input_device(&host, device_id)
+            .or_else(|| host.default_input_device())
+            .ok_or_else(|| "No audio input device is available".to_string())?;
+        let stream = CpalCaptureStream::open(&device, format)?;
+        Ok(Box::new(stream))
+    }
+
+    #[cfg(target_os = "linux")]
+    fn render_loopback_format(&self) -> Result<StreamFormat, String> {
+        Ok(super::pulse_host::render_loopback_format())
+    }
+
+    #[cfg(target_os = "linux")]
+    fn open_render_loopback(
+        &self,
+        target: LoopbackTarget,
+        format: StreamFormat,
+    ) -> Result<Box<dyn CaptureStream>, String> {
+        super::pulse_host::open_render_loopback(target, format)
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn render_loopback_format(&self) -> Result<StreamFormat, String> {
+        Err("Render loopback capture is not supported on this platform".to_string())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn open_render_loopback(
+        &self,
+        _target: LoopbackTarget,
+        _format: StreamFormat,
+    ) -> Result<Box<dyn CaptureStream>, String> {
+        Err("Render loopback capture is not supported on this platform".to_string())
+    }
+}
+
+impl PlaybackBackend for CpalBackend {
+    fn list_output_devices(&self) -> Result<Vec<AudioDeviceInfo>, String> {
+        let host = cpal::default_host();
+        let devices = host.output_devices().map_err(|err| err.to_string())?;
+        Ok(devices
+            .filter_map(|device| device.name().ok())
+            .map(|name| AudioDeviceInfo {
+                id: name.clone(),
+                label: name,
+            })
+            .collect())
+    }
+
+    fn output_format(&self, device_id: Option<&str>) -> Result<StreamFormat, String> {
+        let host = cpal::default_host();
+        let device = find_output_device(&host, device_id)
+            .or_else(|| host.default_output_device())
+            .ok_or_else(|| "No audio output device is available".to_string())?;
+        let config = device
+            .default_output_config()
+            .map_err(|err| err.to_string())?;
+        Ok(StreamFormat::new(config.sample_rate().0, config.channels()))
+    }
+
+    fn open_output(
+        &self,
+        device_id: Option<&str>,
+        format: StreamFormat,
+    ) -> Result<Box<dyn PlaybackStream>, String> {
+        let host = cpal::default_host();
+        let device = find_output_device(&host, device_id)
+            .or_else(|| host.default_output_device())
+            .ok_or_else(|| "No audio output device is available".to_string())?;
+        let stream = CpalPlaybackStream::open(&device, format)?;
+        Ok(Box::new(stream))
+    }
+}
+
+struct CpalCaptureStream {
+    _stream: cpal::Stream,
+    chunk_rx: mpsc::Receiver<Vec<f32>>,
+    failed: Arc<AtomicBool>,
+}
+
+impl CpalCaptureStream {
+    fn open(device: &cpal::Device, format: StreamFormat) -> Result<Self, String> {
+        let config = cpal::StreamConfig {
+            channels: format.channels,
+            sample_rate: cpal::SampleRate(format.sample_rate),
+            buffer_size: cpal::BufferSize::Default,
+        };
+        // Devices that only run at integer formats reject an f32 stream, so the stream is built
+        // in the device's own format and converted in the callback.
+        let sample_format = device
+            .default_input_config()
+            .map(|config| config.sample_format())
+            .unwrap_or(SampleFormat::F32);
+        let (chunk_tx, chunk_rx) = mpsc::sync_channel::<Vec<f32>>(64);
+        let failed = Arc::new(AtomicBool::new(false));
+        let stream = match sample_format {
+            SampleFormat::I8 => build_input::<i8>(device, &config, chunk_tx, &failed),
+            SampleFormat::I16 => build_input::<i16>(device, &config, chunk_tx, &failed),
+            SampleFormat::I32 => build_input::<i32>(device, &config, chunk_tx, &failed),
+            SampleFormat::U8 => build_input::<u8>(device, &config, chunk_tx, &failed),
+            SampleFormat::U16 => build_input::<u16>(device, &config, chunk_tx, &failed),
+            SampleFormat::U32 => build_input::<u32>(device, &config, chunk_tx, &failed),
+            SampleFormat::F32 => build_input::<f32>(device, &config, chunk_tx, &failed),
+            SampleFormat::F64 => build_input::<f64>(device, &config, chunk_tx, &failed),
+            other => Err(format!("Unsupported input sample format: {other}")),
+        }?;
+        stream.play().map_err(|err| err.to_string())?;
+
+        Ok(Self {
+            _stream: stream,
+            chunk_rx,
+            failed,
+        })
+    }
+}
+
+fn build_input<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    chunk_tx: mpsc::SyncSender<Vec<f32>>,
+    failed: &Arc<AtomicBool>,
+) -> Result<cpal::Stream, String>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    let failed = Arc::clone(failed);
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _: &cpal::InputCallbackInfo| {
+                let chunk = data
+                    .iter()
+                    .map(|sample| f32::from_sample(*sample))
+                    .collect();
+                let _ = chunk_tx.try_send(chunk);
+            },
+            move |_err| {
+                failed.store(true, Ordering::Relaxed);
+            },
+            None,
+        )
+        .map_err(|err| err.to_string())
+}
+
+impl CaptureStream for CpalCaptureStream {
+    fn read(&mut self, out: &mut Vec<f32>, timeout: Duration) -> Result<(), String> {
+        if self.failed.load(Ordering::Relaxed) {
+            return Err("Audio input stream failed".to_string());
+        }
+        let chunk = self
+            .chunk_rx
+            .recv_timeout(timeout)
+            .map_err(|err| err.to_string())?;
+        out.extend(chunk.iter().map(|sample| sample.clamp(-1.0, 1.0)));
+        while let Ok(chunk) = self.chunk_rx.try_recv() {
+            out.extend(chunk.iter().map(|sample| sample.clamp(-1.0, 1.0)));
+        }
+        Ok(())
+    }
+}
+
+struct CpalPlaybackStream {
+    _stream: cpal::Stream,
+    queue: Arc<Mutex<VecDeque<f32>>>,
+    max_queued: usize,
+    failed: Arc<AtomicBool>,
+}
+
+impl CpalPlaybackStream {
+    fn open(device: &cpal::Device, format: StreamFormat) -> Result<Self, String> {
+        let config = cpal::StreamConfig {
+            channels: format.channels,
+            sample_rate: cpal::SampleRate(format.sample_rate),
+            buffer_size: cpal::BufferSize::Default,
+        };
+        let sample_format = device
+            .default_output_config()
+            .map(|config| config.sample_format())
+            .unwrap_or(SampleFormat::F32);
+        let queue = Arc::new(Mutex::new(VecDeque::<f32>::new()));
+        let failed = Arc::new(AtomicBool::new(false));
+        let stream = match sample_format {
+            SampleFormat::I8 => build_output::<i8>(device, &config, &queue, &failed),
+            SampleFormat::I16 => build_output::<i16>(device, &config, &queue, &failed),
+            SampleFormat::I32 => build_output::<i32>(device, &config, &queue, &failed),
+            SampleFormat::U8 => build_output::<u8>(device, &config, &queue, &failed),
+            SampleFormat::U16 => build_output::<u16>(device, &config, &queue, &failed),
+            SampleFormat::U32 => build_output::<u32>(device, &config, &queue, &failed),
+            SampleFormat::F32 => build_output::<f32>(device, &config, &queue, &failed),
+            SampleFormat::F64 => build_output::<f64>(device, &config, &queue, &failed),
+            other => Err(format!("Unsupported output sample format: {other}")),
+        }?;
+        stream.play().map_err(|err| err.to_string())?;
+
+        Ok(Self {
+            _stream: stream,
+            queue,
+            // Roughly 100 ms of audio keeps latency low without starving the callback.
+            max_queued: format.sample_rate as usize / 10 * format.channels as usize,
+            failed,
+        })
+    }
+}
+
+fn build_output<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    queue: &Arc<Mutex<VecDeque<f32>>>,
+    failed: &Arc<AtomicBool>,
+) -> Result<cpal::Stream, String>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    let queue = Arc::clone(queue);
+    let failed = Arc::clone(failed);
+    device
+        .build_output_stream(
+            config,
+            move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+                let Ok(mut queue) = queue.lock() else {
+                    data.fill(T::EQUILIBRIUM);
+                    return;
+                };
+                for sample in data.iter_mut() {
+                    *sample = T::from_sample(queue.pop_front().unwrap_or(0.0));
+                }
+            },
+            move |_err| {
+                failed.store(true, Ordering::Relaxed);
+            },
+            None,
+        )
+        .map_err(|err| err.to_string())
+}
+
+impl PlaybackStream for CpalPlaybackStream {
+    fn write(&mut self, samples: &[f32]) -> Result<(), String> {
+        loop {
+            if self.failed.load(Ordering::Relaxed) {
+                return Err("Audio output stream failed".to_string());
+            }
+            let queued = self
+                .queue
+                .lock()
+                .map_err(|_| "Audio output queue poisoned".to_string())?
+                .len();
+            if queued < self.max_queued {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        self.queue
+            .lock()
+            .map_err(|_| "Audio output queue poisoned".to_string())?
+            .extend(samples.iter().map(|sample| sample.clamp(-1.0, 1.0)));
+        Ok(())
+    }
+}
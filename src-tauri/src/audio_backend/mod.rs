@@ -0,0 +1,262 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+mod cpal_host;
+mod null;
+#[cfg(target_os = "linux")]
+mod pulse_host;
+mod resample;
+#[cfg(test)]
+pub(crate) mod test_support;
+#[cfg(target_os = "windows")]
+mod wasapi_host;
+
+pub use null::read_wav_interleaved;
+pub(crate) use null::{wav_samples, NullBackend};
+
+pub(crate) use crate::voice_processing::{SAMPLES_PER_FRAME, SAMPLE_RATE};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StreamFormat {
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+impl StreamFormat {
+    pub const fn new(sample_rate: u32, channels: u16) -> Self {
+        Self {
+            sample_rate,
+            channels,
+        }
+    }
+}
+
+/// Which part of the default render endpoint's mix a loopback capture records.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum LoopbackTarget {
+    /// Everything the endpoint plays.
+    System,
+    /// Only the given process and the processes it started.
+    IncludeProcessTree(u32),
+    /// Everything except the given process and the processes it started.
+    ExcludeProcessTree(u32),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct AudioDeviceInfo {
+    pub id: String,
+    pub label: String,
+}
+
+/// A platform audio API able to open capture streams.
+///
+/// Streams are opened on the thread that reads from them, since the platform handles behind
+/// them (COM objects, ALSA PCMs) are generally not safe to move between threads.
+pub(crate) trait CaptureBackend: Send + Sync {
+    fn list_input_devices(&self) -> Result<Vec<AudioDeviceInfo>, String>;
+
+    /// The id of the current default input device, if there is one.
+    fn default_input_device_id(&self) -> Option<String>;
+
+    /// The format the input device captures in natively, resolving `device_id` the same way
+    /// [`CaptureBackend::open_input`] does.
+    fn input_format(&self, device_id: Option<&str>) -> Result<StreamFormat, String>;
+
+    /// Opens an input device, falling back to the default device when `device_id` is missing
+    /// or no longer available.
+    fn open_input(
+        &self,
+        device_id: Option<&str>,
+        format: StreamFormat,
+    ) -> Result<Box<dyn CaptureStream>, String>;
+
+    /// The native format of the default render endpoint.
+    fn render_loopback_format(&self) -> Result<StreamFormat, String>;
+
+    /// Opens a loopback capture of the default render endpoint, limited to `target`. Backends
+    /// that cannot filter by process fail for anything but [`LoopbackTarget::System`].
+    fn open_render_loopback(
+        &self,
+        target: LoopbackTarget,
+        format: StreamFormat,
+    ) -> Result<Box<dyn CaptureStream>, String>;
+}
+
+pub(crate) trait CaptureStream {
+    /// Waits up to `timeout` for the device and appends the captured interleaved samples to
+    /// `out`. A stream that produced nothing within the timeout is treated as failed.
+    fn read(&mut self, out: &mut Vec<f32>, timeout: Duration) -> Result<(), String>;
+}
+
+/// The output half of a platform audio API. Implemented by the same types as
+/// [`CaptureBackend`] so a backend choice covers both directions.
+pub(crate) trait PlaybackBackend: Send + Sync {
+    fn list_output_devices(&self) -> Result<Vec<AudioDeviceInfo>, String>;
+
+    /// The format the output device plays natively, resolving `device_id` the same way
+    /// [`PlaybackBackend::open_output`] does.
+    fn output_format(&self, device_id: Option<&str>) -> Result<StreamFormat, String>;
+
+    /// Opens an output device, falling back to the default device when `device_id` is missing
+    /// or no longer available.
+    fn open_output(
+        &self,
+        device_id: Option<&str>,
+        format: StreamFormat,
+    ) -> Result<Box<dyn PlaybackStream>, String>;
+}
+
+pub(crate) trait PlaybackStream {
+    /// Queues interleaved samples for playback, blocking while the device buffer is full.
+    fn write(&mut self, samples: &[f32]) -> Result<(), String>;
+}
+
+/// Opens an input device at its native format and converts to `sample_rate` in Rust, so the
+/// result does not depend on the OS converter. `channels` overrides the device's channel
+/// count; extra channels are averaged away, a mono device is copied to every channel. Returns
+/// the stream together with the format it delivers.
+pub(crate) fn open_input_resampled(
+    backend: &dyn CaptureBackend,
+    device_id: Option<&str>,
+    channels: Option<u16>,
+    sample_rate: u32,
+) -> Result<(Box<dyn CaptureStream>, StreamFormat), String> {
+    let device_format = backend.input_format(device_id)?;
+    let stream = backend.open_input(device_id, device_format)?;
+    resample::wrap(stream, device_format, sample_rate, channels)
+}
+
+/// The loopback counterpart of [`open_input_resampled`].
+pub(crate) fn open_render_loopback_resampled(
+    backend: &dyn CaptureBackend,
+    target: LoopbackTarget,
+    channels: Option<u16>,
+    sample_rate: u32,
+) -> Result<(Box<dyn CaptureStream>, StreamFormat), String> {
+    let device_format = backend.render_loopback_format()?;
+    let stream = backend.open_render_loopback(target, device_format)?;
+    resample::wrap(stream, device_format, sample_rate, channels)
+}
+
+/// The playback counterpart of [`open_input_resampled`]: opens an output device at its native
+/// format and converts what is written to it from `format` in Rust.
+pub(crate) fn open_output_resampled(
+    backend: &dyn PlaybackBackend,
+    device_id: Option<&str>,
+    format: StreamFormat,
+) -> Result<Box<dyn PlaybackStream>, String> {
+    let device_format = backend.output_format(device_id)?;
+    let stream = backend.open_output(device_id, device_format)?;
+    resample::wrap_output(stream, format, device_format)
+}
+
+/// Converts a whole interleaved buffer from `from_rate` to `to_rate`, e.g. a clip decoded
+/// from a file.
+pub(crate) fn resample_buffer(
+    samples: &[f32],
+    channels: usize,
+    from_rate: u32,
+    to_rate: u32,
+) -> Result<Vec<f32>, String> {
+    resample::resample_buffer(samples, channels, from_rate, to_rate)
+}
+
+/// Picks the backend for this platform. `CHITCHAT_AUDIO_BACKEND=null` or
+/// `CHITCHAT_AUDIO_BACKEND=file:<path.wav>` swaps in the null backend for headless runs.
+pub(crate) fn default_backend() -> Arc<dyn CaptureBackend> {
+    if let Ok(value) = std::env::var("CHITCHAT_AUDIO_BACKEND") {
+        if value == "null" {
+            return Arc::new(NullBackend::silence(true));
+        }
+        if let Some(path) = value.strip_prefix("file:") {
+            return Arc::new(NullBackend::wav_file(path, true));
+        }
+    }
+    platform_backend()
+}
+
+/// The playback counterpart of [`default_backend`], honouring the same override.
+pub(crate) fn default_playback_backend() -> Arc<dyn PlaybackBackend> {
+    if let Ok(value) = std::env::var("CHITCHAT_AUDIO_BACKEND") {
+        if value == "null" || value.starts_with("file:") {
+            return Arc::new(NullBackend::silence(true));
+        }
+    }
+    platform_backend()
+}
+
+#[cfg(target_os = "windows")]
+fn platform_backend() -> Arc<wasapi_host::WasapiBackend> {
+    Arc::new(wasapi_host::WasapiBackend)
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn platform_backend() -> Arc<cpal_host::CpalBackend> {
+    Arc::new(cpal_host::CpalBackend)
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux", target_os = "macos")))]
+fn platform_backend() -> Arc<NullBackend> {
+    Arc::new(NullBackend::silence(true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_support::write_wav;
+    use super::*;
+
+    fn read_to_end(stream: &mut dyn CaptureStream) -> Vec<f32> {
+        let mut samples = Vec::new();
+        while stream.read(&mut samples, Duration::from_millis(10)).is_ok() {}
+        samples
+    }
+
+    #[test]
+    fn file_input_is_downmixed_to_the_requested_channels() {
+        let frames = SAMPLE_RATE as usize / 2;
+        let path = write_wav(
+            "downmix",
+            SAMPLE_RATE,
+            2,
+            [i16::MAX / 2, i16::MAX / 4].repeat(frames),
+        );
+        let backend = NullBackend::wav_file(&path, false);
+
+        let (mut stream, format) =
+            open_input_resampled(&backend, None, Some(1), SAMPLE_RATE).unwrap();
+        let samples = read_to_end(stream.as_mut());
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(format, StreamFormat::new(SAMPLE_RATE, 1));
+        assert_eq!(samples.len(), frames);
+        assert!(samples.iter().all(|sample| (sample - 0.375).abs() < 1e-3));
+    }
+
+    #[test]
+    fn file_input_is_resampled_to_the_requested_rate() {
+        let tone = (0..44_100).map(|index| {
+            let phase = index as f32 * 440.0 / 44_100.0 * std::f32::consts::TAU;
+            (phase.sin() * 16_000.0) as i16
+        });
+        let path = write_wav("resample", 44_100, 1, tone);
+        let backend = NullBackend::wav_file(&path, false);
+
+        let (mut stream, format) = open_input_resampled(&backend, None, None, SAMPLE_RATE).unwrap();
+        let samples = read_to_end(stream.as_mut());
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(format, StreamFormat::new(SAMPLE_RATE, 1));
+        // One second in, one second out, give or take the resampler's last partial block.
+        let expected = SAMPLE_RATE as usize;
+        assert!(
+            samples.len().abs_diff(expected) <= 2 * SAMPLES_PER_FRAME,
+            "{} samples",
+            samples.len()
+        );
+        let peak = samples
+            .iter()
+            .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+        assert!((0.4..0.6).contains(&peak), "peak {peak}");
+    }
+}
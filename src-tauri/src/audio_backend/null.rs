@@ -0,0 +1,237 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use super::{
+    AudioDeviceInfo, CaptureBackend, CaptureStream, LoopbackTarget, PlaybackBackend,
+    PlaybackStream, StreamFormat, SAMPLE_RATE,
+};
+
+#[derive(Clone)]
+enum NullSource {
+    Silence,
+    WavFile(PathBuf),
+}
+
+/// A device-less backend. It either produces silence forever or plays back a WAV file once,
+/// so the capture pipeline can be driven headlessly and deterministically.
+///
+/// When `paced` is set, samples are released in real time, one 10 ms frame at a time;
+/// otherwise each read returns the next frame immediately.
+#[derive(Clone)]
+pub(crate) struct NullBackend {
+    source: NullSource,
+    paced: bool,
+}
+
+impl NullBackend {
+    pub(crate) fn silence(paced: bool) -> Self {
+        Self {
+            source: NullSource::Silence,
+            paced,
+        }
+    }
+
+    pub(crate) fn wav_file(path: impl Into<PathBuf>, paced: bool) -> Self {
+        Self {
+            source: NullSource::WavFile(path.into()),
+            paced,
+        }
+    }
+
+    fn open(&self, format: StreamFormat) -> Result<Box<dyn CaptureStream>, String> {
+        let samples = match &self.source {
+            NullSource::Silence => None,
+            NullSource::WavFile(path) => Some(read_wav_interleaved(path, format)?),
+        };
+        Ok(Box::new(NullCaptureStream {
+            samples,
+            position: 0,
+            chunk_len: format.sample_rate as usize / 100 * format.channels as usize,
+            chunk_duration: Duration::from_millis(10),
+            paced: self.paced,
+            next_deadline: Instant::now(),
+        }))
+    }
+}
+
+impl CaptureBackend for NullBackend {
+    fn list_input_devices(&self) -> Result<Vec<AudioDeviceInfo>, String> {
+        let label = match &self.source {
+            NullSource::Silence => "Null input (silence)".to_string(),
+            NullSource::WavFile(path) => format!("File input ({})", path.display()),
+        };
+        Ok(vec![AudioDeviceInfo {
+            id: "null".to_string(),
+            label,
+        }])
+    }
+
+    fn default_input_device_id(&self) -> Option<String> {
+        Some("null".to_string())
+    }
+
+    fn input_format(&self, _device_id: Option<&str>) -> Result<StreamFormat, String> {
+        match &self.source {
+            NullSource::Silence => Ok(StreamFormat::new(SAMPLE_RATE, 1)),
+            NullSource::WavFile(path) => hound::WavReader::open(path)
+                .map(|reader| {
+                    let spec = reader.spec();
+                    StreamFormat::new(spec.sample_rate, spec.channels.max(1))
+                })
+                .map_err(|err| err.to_string()),
+        }
+    }
+
+    fn open_input(
+        &self,
+        _device_id: Option<&str>,
+        format: StreamFormat,
+    ) -> Result<Box<dyn CaptureStream>, String> {
+        self.open(format)
+    }
+
+    fn render_loopback_format(&self) -> Result<StreamFormat, String> {
+        Ok(StreamFormat::new(SAMPLE_RATE, 2))
+    }
+
+    fn open_render_loopback(
+        &self,
+        _target: LoopbackTarget,
+        format: StreamFormat,
+    ) -> Result<Box<dyn CaptureStream>, String> {
+        NullBackend::silence(self.paced).open(format)
+    }
+}
+
+impl PlaybackBackend for NullBackend {
+    fn list_output_devices(&self) -> Result<Vec<AudioDeviceInfo>, String> {
+        Ok(vec![AudioDeviceInfo {
+            id: "null".to_string(),
+            label: "Null output".to_string(),
+        }])
+    }
+
+    fn output_format(&self, _device_id: Option<&str>) -> Result<StreamFormat, String> {
+        Ok(StreamFormat::new(SAMPLE_RATE, 2))
+    }
+
+    fn open_output(
+        &self,
+        _device_id: Option<&str>,
+        format: StreamFormat,
+    ) -> Result<Box<dyn PlaybackStream>, String> {
+        Ok(Box::new(NullPlaybackStream {
+            samples_per_second: format.sample_rate as f64 * format.channels.max(1) as f64,
+            paced: self.paced,
+            next_deadline: Instant::now(),
+        }))
+    }
+}
+
+/// Reads a WAV file as interleaved f32 samples, remapping channels to `format`.
+pub fn read_wav_interleaved(path: &Path, format: StreamFormat) -> Result<Vec<f32>, String> {
+    let reader = hound::WavReader::open(path).map_err(|err| err.to_string())?;
+    let spec = reader.spec();
+    if spec.sample_rate != format.sample_rate {
+        return Err(format!(
+            "{} is {} Hz, expected {} Hz",
+            path.display(),
+            spec.sample_rate,
+            format.sample_rate
+        ));
+    }
+    let samples = wav_samples(reader)?;
+
+    let in_channels = spec.channels.max(1) as usize;
+    let out_channels = format.channels.max(1) as usize;
+    if in_channels == out_channels {
+        return Ok(samples);
+    }
+    let mut remapped = Vec::with_capacity(samples.len() / in_channels * out_channels);
+    for frame in samples.chunks_exact(in_channels) {
+        let mono = frame.iter().sum::<f32>() / in_channels as f32;
+        for channel in 0..out_channels {
+            remapped.push(if in_channels == 1 || out_channels == 1 {
+                mono
+            } else {
+                frame.get(channel).copied().unwrap_or(mono)
+            });
+        }
+    }
+    Ok(remapped)
+}
+
+/// Decodes every remaining sample of `reader` to f32, interleaved as stored.
+pub(crate) fn wav_samples<R: Read>(mut reader: hound::WavReader<R>) -> Result<Vec<f32>, String> {
+    let spec = reader.spec();
+    match spec.sample_format {
+        hound::SampleFormat::Float => reader
+            .samples::<f32>()
+            .collect::<Result<_, _>>()
+            .map_err(|err| err.to_string()),
+        hound::SampleFormat::Int => {
+            let scale = (1i64 << (spec.bits_per_sample.max(1) - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|sample| sample.map(|value| value as f32 / scale))
+                .collect::<Result<_, _>>()
+                .map_err(|err| err.to_string())
+        }
+    }
+}
+
+struct NullCaptureStream {
+    samples: Option<Vec<f32>>,
+    position: usize,
+    chunk_len: usize,
+    chunk_duration: Duration,
+    paced: bool,
+    next_deadline: Instant,
+}
+
+impl CaptureStream for NullCaptureStream {
+    fn read(&mut self, out: &mut Vec<f32>, _timeout: Duration) -> Result<(), String> {
+        if self.paced {
+            let now = Instant::now();
+            if self.next_deadline > now {
+                std::thread::sleep(self.next_deadline - now);
+            }
+            self.next_deadline += self.chunk_duration;
+        }
+
+        match &self.samples {
+            None => out.resize(out.len() + self.chunk_len, 0.0),
+            Some(samples) => {
+                if self.position >= samples.len() {
+                    return Err("End of input file".to_string());
+                }
+                let end = (self.position + self.chunk_len).min(samples.len());
+                out.extend_from_slice(&samples[self.position..end]);
+                self.position = end;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Discards everything written to it, optionally at the rate a real device would consume it.
+struct NullPlaybackStream {
+    samples_per_second: f64,
+    paced: bool,
+    next_deadline: Instant,
+}
+
+impl PlaybackStream for NullPlaybackStream {
+    fn write(&mut self, samples: &[f32]) -> Result<(), String> {
+        if self.paced {
+            let now = Instant::now();
+            if self.next_deadline > now {
+                std::thread::sleep(self.next_deadline - now);
+            }
+            self.next_deadline = self.next_deadline.max(now)
+                + Duration::from_secs_f64(samples.len() as f64 / self.samples_per_second);
+        }
+        Ok(())
+    }
+}
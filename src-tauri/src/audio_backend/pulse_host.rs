@@ -0,0 +1,166 @@
+use std::sync::mpsc::{self, TrySendError};
+use std::time::Duration;
+
+use libpulse_binding::{
+    def::BufferAttr,
+    sample::{Format, Spec},
+    stream::Direction,
+};
+use libpulse_simple_binding::Simple;
+
+use super::{CaptureStream, LoopbackTarget, StreamFormat, SAMPLE_RATE};
+
+/// PulseAudio, and PipeWire through pipewire-pulse, resolve this to the monitor of whatever the
+/// default sink is when the stream connects.
+const DEFAULT_MONITOR: &str = "@DEFAULT_MONITOR@";
+
+/// The server converts to whatever we ask for, so the monitor is always read at the rate the
+/// rest of the pipeline runs at.
+pub(super) fn render_loopback_format() -> StreamFormat {
+    StreamFormat::new(SAMPLE_RATE, 2)
+}
+
+/// Records the default sink's monitor source, i.e. everything the system is playing. Picking
+/// out or leaving out single applications is not supported, so the setting that leaves this
+/// app's own playback out of a screen share is hidden on Linux. Without sound hardware, a null sink
+/// (`pactl load-module module-null-sink`) set as the default works the same way; the ignored
+/// `reads_the_monitor_of_a_null_sink` test below checks exactly that.
+pub(super) fn open_render_loopback(
+    target: LoopbackTarget,
+    format: StreamFormat,
+) -> Result<Box<dyn CaptureStream>, String> {
+    if target != LoopbackTarget::System {
+        return Err("Per-application loopback capture is not supported on Linux".to_string());
+    }
+    let stream = PulseMonitorStream::open(format)?;
+    Ok(Box::new(stream))
+}
+
+struct PulseMonitorStream {
+    chunk_rx: mpsc::Receiver<Vec<f32>>,
+}
+
+impl PulseMonitorStream {
+    /// Connects on a reader thread of its own, since the simple API only offers blocking reads
+    /// and [`CaptureStream::read`] has to honour its timeout.
+    fn open(format: StreamFormat) -> Result<Self, String> {
+        let spec = Spec {
+            format: Format::FLOAT32NE,
+            channels: format.channels.clamp(1, 32) as u8,
+            rate: format.sample_rate,
+        };
+        if !spec.is_valid() {
+            return Err(format!("Unsupported loopback format: {format:?}"));
+        }
+        // 10 ms per read, matching the frame size downstream.
+        let chunk_samples = format.sample_rate as usize / 100 * spec.channels as usize;
+        let buffer_attr = BufferAttr {
+            maxlength: u32::MAX,
+            tlength: u32::MAX,
+            prebuf: u32::MAX,
+            minreq: u32::MAX,
+            fragsize: (chunk_samples * std::mem::size_of::<f32>()) as u32,
+        };
+
+        let (opened_tx, opened_rx) = mpsc::channel::<Result<(), String>>();
+        let (chunk_tx, chunk_rx) = mpsc::sync_channel::<Vec<f32>>(64);
+        std::thread::Builder::new()
+            .name("pulse-monitor-capture".to_string())
+            .spawn(move || {
+                let simple = match Simple::new(
+                    None,
+                    "ChitChat",
+                    Direction::Record,
+                    Some(DEFAULT_MONITOR),
+                    "System audio capture",
+                    &spec,
+                    None,
+                    Some(&buffer_attr),
+                ) {
+                    Ok(simple) => simple,
+                    Err(err) => {
+                        let _ = opened_tx.send(Err(err.to_string()));
+                        return;
+                    }
+                };
+                let _ = opened_tx.send(Ok(()));
+
+                let mut bytes = vec![0u8; chunk_samples * std::mem::size_of::<f32>()];
+                loop {
+                    if simple.read(&mut bytes).is_err() {
+                        break;
+                    }
+                    let chunk = bytes
+                        .chunks_exact(4)
+                        .map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+                        .collect();
+                    // A full queue drops the chunk; a dropped stream ends the thread.
+                    if let Err(TrySendError::Disconnected(_)) = chunk_tx.try_send(chunk) {
+                        break;
+                    }
+                }
+            })
+            .map_err(|err| err.to_string())?;
+
+        opened_rx
+            .recv_timeout(Duration::from_secs(3))
+            .map_err(|_| "Timed out connecting to the sound server".to_string())??;
+        Ok(Self { chunk_rx })
+    }
+}
+
+impl CaptureStream for PulseMonitorStream {
+    fn read(&mut self, out: &mut Vec<f32>, timeout: Duration) -> Result<(), String> {
+        let chunk = self
+            .chunk_rx
+            .recv_timeout(timeout)
+            .map_err(|err| err.to_string())?;
+        out.extend(chunk.iter().map(|sample| sample.clamp(-1.0, 1.0)));
+        while let Ok(chunk) = self.chunk_rx.try_recv() {
+            out.extend(chunk.iter().map(|sample| sample.clamp(-1.0, 1.0)));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+
+    use super::*;
+
+    fn pactl(args: &[&str]) -> String {
+        let output = Command::new("pactl").args(args).output().expect("pactl");
+        assert!(output.status.success(), "pactl {args:?} failed");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    /// Run with `cargo test -- --ignored` on a machine with a PulseAudio or pipewire-pulse
+    /// server. Swaps a null sink in as the default for the duration of the test.
+    #[test]
+    #[ignore = "needs a running PulseAudio or PipeWire server and pactl"]
+    fn reads_the_monitor_of_a_null_sink() {
+        let previous_sink = pactl(&["get-default-sink"]);
+        let module = pactl(&[
+            "load-module",
+            "module-null-sink",
+            "sink_name=chitchat_test_sink",
+        ]);
+        pactl(&["set-default-sink", "chitchat_test_sink"]);
+
+        let format = render_loopback_format();
+        let read = open_render_loopback(LoopbackTarget::System, format).and_then(|mut stream| {
+            let mut samples = Vec::new();
+            stream.read(&mut samples, Duration::from_secs(2))?;
+            Ok(samples)
+        });
+
+        pactl(&["set-default-sink", &previous_sink]);
+        pactl(&["unload-module", &module]);
+        let samples = read.unwrap();
+        assert!(!samples.is_empty());
+        assert_eq!(samples.len() % format.channels as usize, 0);
+        // Nothing plays into the null sink, so its monitor is silent.
+        assert!(samples.iter().all(|sample| sample.abs() < 1e-4));
+    }
+}
@@ -0,0 +1,340 @@
+use std::time::Duration;
+
+use rubato::{FftFixedOut, Resampler};
+
+use super::{CaptureStream, PlaybackStream, StreamFormat};
+
+/// Wraps `stream`, which captures at `device_format`, so it delivers `sample_rate` with
+/// `channels` channels, or the device's own channel count when `channels` is `None`.
+pub(super) fn wrap(
+    stream: Box<dyn CaptureStream>,
+    device_format: StreamFormat,
+    sample_rate: u32,
+    channels: Option<u16>,
+) -> Result<(Box<dyn CaptureStream>, StreamFormat), String> {
+    let format = StreamFormat::new(sample_rate, channels.unwrap_or(device_format.channels));
+    if device_format == format {
+        return Ok((stream, format));
+    }
+    let stream = ResampledCaptureStream::new(stream, device_format, format)?;
+    Ok((Box::new(stream), format))
+}
+
+/// Wraps `stream`, which plays at `device_format`, so it accepts samples in `format`.
+pub(super) fn wrap_output(
+    stream: Box<dyn PlaybackStream>,
+    format: StreamFormat,
+    device_format: StreamFormat,
+) -> Result<Box<dyn PlaybackStream>, String> {
+    if format == device_format {
+        return Ok(stream);
+    }
+    Ok(Box::new(ResampledPlaybackStream::new(
+        stream,
+        format,
+        device_format,
+    )?))
+}
+
+pub(super) fn resample_buffer(
+    samples: &[f32],
+    channels: usize,
+    from_rate: u32,
+    to_rate: u32,
+) -> Result<Vec<f32>, String> {
+    let channels = channels.max(1);
+    if from_rate == to_rate {
+        return Ok(samples.to_vec());
+    }
+    let mut resampler = FftFixedOut::<f32>::new(
+        from_rate as usize,
+        to_rate as usize,
+        to_rate as usize / 100,
+        1,
+        channels,
+    )
+    .map_err(|err| err.to_string())?;
+    let frames = samples.len() / channels;
+    let expected = (frames as u64 * to_rate as u64 / from_rate.max(1) as u64) as usize;
+    // The resampler's filter delays its output; skip that much and pad the input with silence
+    // until the tail has come through.
+    let delay = resampler.output_delay();
+    let mut planar_in = (0..channels)
+        .map(|channel| {
+            samples
+                .iter()
+                .skip(channel)
+                .step_by(channels)
+                .copied()
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let mut planar_out = resampler.output_buffer_allocate(true);
+    let mut out = Vec::with_capacity(expected * channels);
+    let mut offset = 0;
+    let mut produced = 0;
+    while produced < delay + expected {
+        let needed = resampler.input_frames_next();
+        for buffer in planar_in.iter_mut() {
+            if buffer.len() < offset + needed {
+                buffer.resize(offset + needed, 0.0);
+            }
+        }
+        let input = planar_in
+            .iter()
+            .map(|buffer| &buffer[offset..offset + needed])
+            .collect::<Vec<_>>();
+        let (consumed, written) = resampler
+            .process_into_buffer(&input, &mut planar_out, None)
+            .map_err(|err| err.to_string())?;
+        offset += consumed;
+        for index in 0..written {
+            if produced >= delay && produced < delay + expected {
+                for buffer in &planar_out {
+                    out.push(buffer[index]);
+                }
+            }
+            produced += 1;
+        }
+    }
+    Ok(out)
+}
+
+/// Converts a capture stream to another sample rate and channel count, mapping channels the
+/// same way [`ResampledPlaybackStream`] does. While resampling, output comes in 10 ms blocks,
+/// so a read may return nothing until enough input has been buffered for the first one.
+struct ResampledCaptureStream {
+    inner: Box<dyn CaptureStream>,
+    resampler: Option<FftFixedOut<f32>>,
+    device_channels: usize,
+    channels: usize,
+    interleaved: Vec<f32>,
+    planar_in: Vec<Vec<f32>>,
+    planar_out: Vec<Vec<f32>>,
+}
+
+impl ResampledCaptureStream {
+    fn new(
+        inner: Box<dyn CaptureStream>,
+        device_format: StreamFormat,
+        format: StreamFormat,
+    ) -> Result<Self, String> {
+        let device_channels = device_format.channels.max(1) as usize;
+        let resampler = if device_format.sample_rate == format.sample_rate {
+            None
+        } else {
+            Some(
+                FftFixedOut::<f32>::new(
+                    device_format.sample_rate as usize,
+                    format.sample_rate as usize,
+                    format.sample_rate as usize / 100,
+                    1,
+                    device_channels,
+                )
+                .map_err(|err| err.to_string())?,
+            )
+        };
+        let planar_out = resampler
+            .as_ref()
+            .map(|resampler| resampler.output_buffer_allocate(true))
+            .unwrap_or_default();
+        Ok(Self {
+            inner,
+            resampler,
+            device_channels,
+            channels: format.channels.max(1) as usize,
+            interleaved: Vec::new(),
+            planar_in: vec![Vec::new(); device_channels],
+            planar_out,
+        })
+    }
+}
+
+impl CaptureStream for ResampledCaptureStream {
+    fn read(&mut self, out: &mut Vec<f32>, timeout: Duration) -> Result<(), String> {
+        self.interleaved.clear();
+        self.inner.read(&mut self.interleaved, timeout)?;
+        let Some(resampler) = self.resampler.as_mut() else {
+            for frame in self.interleaved.chunks_exact(self.device_channels) {
+                remap_frame(frame, self.channels, out);
+            }
+            return Ok(());
+        };
+
+        for frame in self.interleaved.chunks_exact(self.device_channels) {
+            for (buffer, sample) in self.planar_in.iter_mut().zip(frame) {
+                buffer.push(*sample);
+            }
+        }
+        let mut frame = vec![0.0; self.device_channels];
+        while self.planar_in[0].len() >= resampler.input_frames_next() {
+            let (consumed, produced) = resampler
+                .process_into_buffer(&self.planar_in, &mut self.planar_out, None)
+                .map_err(|err| err.to_string())?;
+            for buffer in self.planar_in.iter_mut() {
+                buffer.drain(..consumed);
+            }
+            for index in 0..produced {
+                for (sample, buffer) in frame.iter_mut().zip(&self.planar_out) {
+                    *sample = buffer[index].clamp(-1.0, 1.0);
+                }
+                remap_frame(&frame, self.channels, out);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Converts what is written to it to the sample rate and channel count of the device behind
+/// `inner`. Mono is copied to every device channel; wider input keeps its first channels and
+/// leaves the rest silent, or is averaged for a mono device.
+struct ResampledPlaybackStream {
+    inner: Box<dyn PlaybackStream>,
+    resampler: Option<FftFixedOut<f32>>,
+    channels: usize,
+    device_channels: usize,
+    planar_in: Vec<Vec<f32>>,
+    planar_out: Vec<Vec<f32>>,
+    interleaved: Vec<f32>,
+}
+
+impl ResampledPlaybackStream {
+    fn new(
+        inner: Box<dyn PlaybackStream>,
+        format: StreamFormat,
+        device_format: StreamFormat,
+    ) -> Result<Self, String> {
+        let channels = format.channels.max(1) as usize;
+        let resampler = if format.sample_rate == device_format.sample_rate {
+            None
+        } else {
+            Some(
+                FftFixedOut::<f32>::new(
+                    format.sample_rate as usize,
+                    device_format.sample_rate as usize,
+                    device_format.sample_rate as usize / 100,
+                    1,
+                    channels,
+                )
+                .map_err(|err| err.to_string())?,
+            )
+        };
+        let planar_out = resampler
+            .as_ref()
+            .map(|resampler| resampler.output_buffer_allocate(true))
+            .unwrap_or_default();
+        Ok(Self {
+            inner,
+            resampler,
+            channels,
+            device_channels: device_format.channels.max(1) as usize,
+            planar_in: vec![Vec::new(); channels],
+            planar_out,
+            interleaved: Vec::new(),
+        })
+    }
+}
+
+/// Appends one frame of `frame.len()` channels to `out` with `device_channels` channels.
+fn remap_frame(frame: &[f32], device_channels: usize, out: &mut Vec<f32>) {
+    match (frame.len(), device_channels) {
+        (1, _) => out.extend(std::iter::repeat_n(frame[0], device_channels)),
+        (channels, 1) => out.push(frame.iter().sum::<f32>() / channels as f32),
+        _ => out
+            .extend((0..device_channels).map(|channel| frame.get(channel).copied().unwrap_or(0.0))),
+    }
+}
+
+impl PlaybackStream for ResampledPlaybackStream {
+    fn write(&mut self, samples: &[f32]) -> Result<(), String> {
+        self.interleaved.clear();
+        let Some(resampler) = self.resampler.as_mut() else {
+            for frame in samples.chunks_exact(self.channels) {
+                remap_frame(frame, self.device_channels, &mut self.interleaved);
+            }
+            return self.inner.write(&self.interleaved);
+        };
+
+        for frame in samples.chunks_exact(self.channels) {
+            for (buffer, sample) in self.planar_in.iter_mut().zip(frame) {
+                buffer.push(*sample);
+            }
+        }
+        let mut frame = vec![0.0; self.channels];
+        while self.planar_in[0].len() >= resampler.input_frames_next() {
+            let (consumed, produced) = resampler
+                .process_into_buffer(&self.planar_in, &mut self.planar_out, None)
+                .map_err(|err| err.to_string())?;
+            for buffer in self.planar_in.iter_mut() {
+                buffer.drain(..consumed);
+            }
+            for index in 0..produced {
+                for (sample, buffer) in frame.iter_mut().zip(&self.planar_out) {
+                    *sample = buffer[index];
+                }
+                remap_frame(&frame, self.device_channels, &mut self.interleaved);
+            }
+        }
+        // The resampler hands out whole 10 ms blocks, so a short write may produce nothing yet.
+        if self.interleaved.is_empty() {
+            return Ok(());
+        }
+        self.inner.write(&self.interleaved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    struct RecordingStream(Arc<Mutex<Vec<f32>>>);
+
+    impl PlaybackStream for RecordingStream {
+        fn write(&mut self, samples: &[f32]) -> Result<(), String> {
+            self.0.lock().unwrap().extend_from_slice(samples);
+            Ok(())
+        }
+    }
+
+    fn written_to(format: StreamFormat, device_format: StreamFormat, samples: &[f32]) -> Vec<f32> {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let inner = Box::new(RecordingStream(Arc::clone(&written)));
+        let mut stream = wrap_output(inner, format, device_format).unwrap();
+        stream.write(samples).unwrap();
+        let written = written.lock().unwrap().clone();
+        written
+    }
+
+    #[test]
+    fn output_upmixes_mono_to_every_device_channel() {
+        let written = written_to(
+            StreamFormat::new(48_000, 1),
+            StreamFormat::new(48_000, 3),
+            &[0.1, 0.2],
+        );
+        assert_eq!(written, [0.1, 0.1, 0.1, 0.2, 0.2, 0.2]);
+    }
+
+    #[test]
+    fn output_averages_stereo_for_a_mono_device() {
+        let written = written_to(
+            StreamFormat::new(48_000, 2),
+            StreamFormat::new(48_000, 1),
+            &[0.2, 0.4, -0.2, 0.0],
+        );
+        assert_eq!(written.len(), 2);
+        assert!((written[0] - 0.3).abs() < 1e-6 && (written[1] + 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn output_keeps_stereo_on_a_wider_device() {
+        let written = written_to(
+            StreamFormat::new(48_000, 2),
+            StreamFormat::new(48_000, 4),
+            &[0.2, 0.4],
+        );
+        assert_eq!(written, [0.2, 0.4, 0.0, 0.0]);
+    }
+}
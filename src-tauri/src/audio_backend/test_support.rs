@@ -0,0 +1,26 @@
+//! Helpers shared by the audio tests.
+
+use std::path::PathBuf;
+
+/// Writes interleaved 16-bit `samples` to a WAV file in the temp directory and returns its
+/// path. `name` keeps files of different tests apart; callers remove the file when done.
+pub(crate) fn write_wav(
+    name: &str,
+    sample_rate: u32,
+    channels: u16,
+    samples: impl IntoIterator<Item = i16>,
+) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("chitchat-{}-{name}.wav", std::process::id()));
+    let spec = hound::WavSpec {
+        channels,
+        sample_rate,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(&path, spec).unwrap();
+    for sample in samples {
+        writer.write_sample(sample).unwrap();
+    }
+    writer.finalize().unwrap();
+    path
+}
@@ -0,0 +1,341 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use wasapi::{
+    initialize_mta, AudioCaptureClient, AudioClient, AudioRenderClient, DeviceEnumerator,
+    Direction as AudioDirection, Handle, SampleType, StreamMode, WaveFormat,
+};
+
+use super::{
+    AudioDeviceInfo, CaptureBackend, CaptureStream, LoopbackTarget, PlaybackBackend,
+    PlaybackStream, StreamFormat,
+};
+
+/// Process loopback clients have no device period to ask for, so they get a fixed 20 ms buffer.
+const PROCESS_LOOPBACK_BUFFER_HNS: i64 = 200_000;
+
+pub(super) struct WasapiBackend;
+
+fn resolve_device(
+    enumerator: &DeviceEnumerator,
+    device_id: Option<&str>,
+    direction: AudioDirection,
+) -> Result<wasapi::Device, String> {
+    if let Some(device_id) = device_id {
+        if !device_id.is_empty() {
+            if let Ok(device) = enumerator.get_device(device_id) {
+                return Ok(device);
+            }
+        }
+    }
+    enumerator
+        .get_default_device(&direction)
+        .map_err(|err| err.to_string())
+}
+
+fn list_devices(direction: AudioDirection) -> Result<Vec<AudioDeviceInfo>, String> {
+    initialize_mta().ok().map_err(|err| err.to_string())?;
+    let enumerator = DeviceEnumerator::new().map_err(|err| err.to_string())?;
+    let devices = enumerator
+        .get_device_collection(&direction)
+        .map_err(|err| err.to_string())?;
+    let mut result = Vec::new();
+    for device in (&devices).into_iter().flatten() {
+        result.push(AudioDeviceInfo {
+            id: device.get_id().map_err(|err| err.to_string())?,
+            label: device.get_friendlyname().map_err(|err| err.to_string())?,
+        });
+    }
+    Ok(result)
+}
+
+/// The shared-mode mix format, which is what the audio engine runs the device at.
+fn mix_format(device: &wasapi::Device) -> Result<StreamFormat, String> {
+    let audio_client = device.get_iaudioclient().map_err(|err| err.to_string())?;
+    let format = audio_client
+        .get_mixformat()
+        .map_err(|err| err.to_string())?;
+    Ok(StreamFormat::new(
+        format.get_samplespersec(),
+        format.get_nchannels(),
+    ))
+}
+
+/// A 32-bit float format at `format`. Without the OS converter a shared-mode stream has to
+/// match the mix format exactly, so the channel mask is taken from `audio_client`'s mix format
+/// when it has the same channel count. Process loopback clients have no mix format and accept
+/// any layout.
+fn float_wave_format(audio_client: &AudioClient, format: StreamFormat) -> WaveFormat {
+    let channel_mask = audio_client
+        .get_mixformat()
+        .ok()
+        .filter(|mix| mix.get_nchannels() == format.channels)
+        .map(|mix| mix.get_dwchannelmask());
+    WaveFormat::new(
+        32,
+        32,
+        &SampleType::Float,
+        format.sample_rate as usize,
+        format.channels as usize,
+        channel_mask,
+    )
+}
+
+impl CaptureBackend for WasapiBackend {
+    fn list_input_devices(&self) -> Result<Vec<AudioDeviceInfo>, String> {
+        list_devices(AudioDirection::Capture)
+    }
+
+    fn default_input_device_id(&self) -> Option<String> {
+        initialize_mta().ok().ok()?;
+        DeviceEnumerator::new()
+            .ok()?
+            .get_default_device(&AudioDirection::Capture)
+            .ok()?
+            .get_id()
+            .ok()
+    }
+
+    fn input_format(&self, device_id: Option<&str>) -> Result<StreamFormat, String> {
+        initialize_mta().ok().map_err(|err| err.to_string())?;
+        let enumerator = DeviceEnumerator::new().map_err(|err| err.to_string())?;
+        let device = resolve_device(&enumerator, device_id, AudioDirection::Capture)?;
+        mix_format(&device)
+    }
+
+    fn open_input(
+        &self,
+        device_id: Option<&str>,
+        format: StreamFormat,
+    ) -> Result<Box<dyn CaptureStream>, String> {
+        initialize_mta().ok().map_err(|err| err.to_string())?;
+        let enumerator = DeviceEnumerator::new().map_err(|err| err.to_string())?;
+        let device = resolve_device(&enumerator, device_id, AudioDirection::Capture)?;
+        let stream = WasapiCaptureStream::open(&device, format)?;
+        Ok(Box::new(stream))
+    }
+
+    fn render_loopback_format(&self) -> Result<StreamFormat, String> {
+        initialize_mta().ok().map_err(|err| err.to_string())?;
+        let enumerator = DeviceEnumerator::new().map_err(|err| err.to_string())?;
+        let device = enumerator
+            .get_default_device(&AudioDirection::Render)
+            .map_err(|err| err.to_string())?;
+        mix_format(&device)
+    }
+
+    fn open_render_loopback(
+        &self,
+        target: LoopbackTarget,
+        format: StreamFormat,
+    ) -> Result<Box<dyn CaptureStream>, String> {
+        initialize_mta().ok().map_err(|err| err.to_string())?;
+        let stream = match target {
+            LoopbackTarget::System => {
+                let enumerator = DeviceEnumerator::new().map_err(|err| err.to_string())?;
+                let device = enumerator
+                    .get_default_device(&AudioDirection::Render)
+                    .map_err(|err| err.to_string())?;
+                WasapiCaptureStream::open(&device, format)?
+            }
+            LoopbackTarget::IncludeProcessTree(process_id)
+            | LoopbackTarget::ExcludeProcessTree(process_id) => {
+                // Process loopback requires Windows 10 2004 or later. `include_tree` picks
+                // between its include and exclude modes.
+                let include_tree = matches!(target, LoopbackTarget::IncludeProcessTree(_));
+                let audio_client =
+                    AudioClient::new_application_loopback_client(process_id, include_tree)
+                        .map_err(|err| err.to_string())?;
+                WasapiCaptureStream::from_client(audio_client, format, PROCESS_LOOPBACK_BUFFER_HNS)?
+            }
+        };
+        Ok(Box::new(stream))
+    }
+}
+
+impl PlaybackBackend for WasapiBackend {
+    fn list_output_devices(&self) -> Result<Vec<AudioDeviceInfo>, String> {
+        list_devices(AudioDirection::Render)
+    }
+
+    fn output_format(&self, device_id: Option<&str>) -> Result<StreamFormat, String> {
+        initialize_mta().ok().map_err(|err| err.to_string())?;
+        let enumerator = DeviceEnumerator::new().map_err(|err| err.to_string())?;
+        let device = resolve_device(&enumerator, device_id, AudioDirection::Render)?;
+        mix_format(&device)
+    }
+
+    fn open_output(
+        &self,
+        device_id: Option<&str>,
+        format: StreamFormat,
+    ) -> Result<Box<dyn PlaybackStream>, String> {
+        initialize_mta().ok().map_err(|err| err.to_string())?;
+        let enumerator = DeviceEnumerator::new().map_err(|err| err.to_string())?;
+        let device = resolve_device(&enumerator, device_id, AudioDirection::Render)?;
+        let stream = WasapiPlaybackStream::open(&device, format)?;
+        Ok(Box::new(stream))
+    }
+}
+
+struct WasapiCaptureStream {
+    audio_client: AudioClient,
+    capture_client: AudioCaptureClient,
+    h_event: Handle,
+    byte_queue: VecDeque<u8>,
+}
+
+impl WasapiCaptureStream {
+    /// Opens a shared-mode float stream. Capture devices are read directly, render devices
+    /// through loopback. `format` has to be the device's mix format; conversions happen in
+    /// [`super::resample`], not in WASAPI.
+    fn open(device: &wasapi::Device, format: StreamFormat) -> Result<Self, String> {
+        let audio_client = device.get_iaudioclient().map_err(|err| err.to_string())?;
+        let (_, min_time) = audio_client
+            .get_device_period()
+            .map_err(|err| err.to_string())?;
+        Self::from_client(audio_client, format, min_time)
+    }
+
+    fn from_client(
+        mut audio_client: AudioClient,
+        format: StreamFormat,
+        buffer_duration_hns: i64,
+    ) -> Result<Self, String> {
+        let desired_format = float_wave_format(&audio_client, format);
+        let mode = StreamMode::EventsShared {
+            autoconvert: false,
+            buffer_duration_hns,
+        };
+        audio_client
+            .initialize_client(&desired_format, &AudioDirection::Capture, &mode)
+            .map_err(|err| err.to_string())?;
+
+        let h_event = audio_client
+            .set_get_eventhandle()
+            .map_err(|err| err.to_string())?;
+        let capture_client = audio_client
+            .get_audiocaptureclient()
+            .map_err(|err| err.to_string())?;
+        audio_client.start_stream().map_err(|err| err.to_string())?;
+
+        Ok(Self {
+            audio_client,
+            capture_client,
+            h_event,
+            byte_queue: VecDeque::new(),
+        })
+    }
+}
+
+impl CaptureStream for WasapiCaptureStream {
+    fn read(&mut self, out: &mut Vec<f32>, timeout: Duration) -> Result<(), String> {
+        self.h_event
+            .wait_for_event(timeout.as_millis() as u32)
+            .map_err(|err| err.to_string())?;
+
+        loop {
+            let new_frames = self
+                .capture_client
+                .get_next_packet_size()
+                .map_err(|err| err.to_string())?
+                .unwrap_or(0);
+            if new_frames == 0 {
+                break;
+            }
+            self.capture_client
+                .read_from_device_to_deque(&mut self.byte_queue)
+                .map_err(|err| err.to_string())?;
+        }
+
+        let whole = self.byte_queue.len() / 4 * 4;
+        let bytes = self.byte_queue.make_contiguous();
+        out.extend(
+            bytes[..whole]
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]).clamp(-1.0, 1.0)),
+        );
+        self.byte_queue.drain(..whole);
+        Ok(())
+    }
+}
+
+impl Drop for WasapiCaptureStream {
+    fn drop(&mut self) {
+        let _ = self.audio_client.stop_stream();
+    }
+}
+
+struct WasapiPlaybackStream {
+    audio_client: AudioClient,
+    render_client: AudioRenderClient,
+    h_event: Handle,
+    block_align: usize,
+    byte_queue: VecDeque<u8>,
+}
+
+impl WasapiPlaybackStream {
+    fn open(device: &wasapi::Device, format: StreamFormat) -> Result<Self, String> {
+        let mut audio_client = device.get_iaudioclient().map_err(|err| err.to_string())?;
+        let desired_format = float_wave_format(&audio_client, format);
+        let (_, min_time) = audio_client
+            .get_device_period()
+            .map_err(|err| err.to_string())?;
+        let mode = StreamMode::EventsShared {
+            autoconvert: false,
+            buffer_duration_hns: min_time,
+        };
+        audio_client
+            .initialize_client(&desired_format, &AudioDirection::Render, &mode)
+            .map_err(|err| err.to_string())?;
+
+        let h_event = audio_client
+            .set_get_eventhandle()
+            .map_err(|err| err.to_string())?;
+        let render_client = audio_client
+            .get_audiorenderclient()
+            .map_err(|err| err.to_string())?;
+        audio_client.start_stream().map_err(|err| err.to_string())?;
+
+        Ok(Self {
+            audio_client,
+            render_client,
+            h_event,
+            block_align: desired_format.get_blockalign() as usize,
+            byte_queue: VecDeque::new(),
+        })
+    }
+}
+
+impl PlaybackStream for WasapiPlaybackStream {
+    fn write(&mut self, samples: &[f32]) -> Result<(), String> {
+        for sample in samples {
+            self.byte_queue
+                .extend(sample.clamp(-1.0, 1.0).to_le_bytes());
+        }
+
+        while self.byte_queue.len() >= self.block_align {
+            let available = self
+                .audio_client
+                .get_available_space_in_frames()
+                .map_err(|err| err.to_string())? as usize;
+            let frames = available.min(self.byte_queue.len() / self.block_align);
+            if frames == 0 {
+                self.h_event
+                    .wait_for_event(1000)
+                    .map_err(|err| err.to_string())?;
+                continue;
+            }
+            self.render_client
+                .write_to_device_from_deque(frames, &mut self.byte_queue, None)
+                .map_err(|err| err.to_string())?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for WasapiPlaybackStream {
+    fn drop(&mut self) {
+        let _ = self.audio_client.stop_stream();
+    }
+}
@@ -0,0 +1,284 @@
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tokio::sync::Notify;
+
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AudioRingStats {
+    /// Frames dropped because the consumer fell behind.
+    pub overruns: u64,
+    /// Times the consumer waited a full stall timeout without getting a frame.
+    pub underruns: u64,
+    pub queued_frames: usize,
+    pub capacity_frames: usize,
+}
+
+/// A preallocated single-producer/single-consumer ring of fixed-size audio frames.
+///
+/// Neither side blocks or allocates. When the ring is full the producer drops the oldest
+/// frames, so latency stays bounded by the capacity instead of memory growing behind a stalled
+/// consumer. Both sides move the read position with compare-and-swap; a consumer whose frame
+/// was dropped while it was copying it notices the moved position and retries, which is why
+/// the samples themselves live in atomics.
+pub(crate) struct AudioRing {
+    slots: Box<[AtomicU32]>,
+    frame_len: usize,
+    /// Read and write positions in samples. They only ever grow and are always multiples of
+    /// `frame_len`; slots are addressed modulo the capacity.
+    head: AtomicUsize,
+    tail: AtomicUsize,
+    overruns: AtomicU64,
+    underruns: AtomicU64,
+    closed: AtomicBool,
+    readable: Notify,
+}
+
+impl AudioRing {
+    pub(crate) fn new(frame_len: usize, capacity_frames: usize) -> Self {
+        let frame_len = frame_len.max(1);
+        let slots = (0..frame_len * capacity_frames.max(1))
+            .map(|_| AtomicU32::new(0))
+            .collect();
+        Self {
+            slots,
+            frame_len,
+            head: AtomicUsize::new(0),
+            tail: AtomicUsize::new(0),
+            overruns: AtomicU64::new(0),
+            underruns: AtomicU64::new(0),
+            closed: AtomicBool::new(false),
+            readable: Notify::new(),
+        }
+    }
+
+    pub(crate) fn frame_len(&self) -> usize {
+        self.frame_len
+    }
+
+    /// Appends one frame of exactly [`AudioRing::frame_len`] samples. Producer side only.
+    pub(crate) fn push_frame(&self, frame: &[f32]) {
+        debug_assert_eq!(frame.len(), self.frame_len);
+        let capacity = self.slots.len();
+        let tail = self.tail.load(Ordering::Relaxed);
+        let mut head = self.head.load(Ordering::Acquire);
+        while tail + self.frame_len - head > capacity {
+            let next_head = tail + self.frame_len - capacity;
+            match self.head.compare_exchange_weak(
+                head,
+                next_head,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    self.overruns.fetch_add(
+                        ((next_head - head) / self.frame_len) as u64,
+                        Ordering::Relaxed,
+                    );
+                    break;
+                }
+                Err(current) => head = current,
+            }
+        }
+
+        for (offset, sample) in frame.iter().take(self.frame_len).enumerate() {
+            self.slots[(tail + offset) % capacity].store(sample.to_bits(), Ordering::Relaxed);
+        }
+        self.tail.store(tail + self.frame_len, Ordering::Release);
+        self.readable.notify_one();
+    }
+
+    /// Copies the oldest frame into `out` and removes it. Returns `false` when the ring is
+    /// empty. Consumer side only.
+    pub(crate) fn pop_frame(&self, out: &mut [f32]) -> bool {
+        let capacity = self.slots.len();
+        loop {
+            let head = self.head.load(Ordering::Acquire);
+            let tail = self.tail.load(Ordering::Acquire);
+            if tail - head < self.frame_len {
+                return false;
+            }
+            for (offset, sample) in out.iter_mut().take(self.frame_len).enumerate() {
+                *sample =
+                    f32::from_bits(self.slots[(head + offset) % capacity].load(Ordering::Relaxed));
+            }
+            if self
+                .head
+                .compare_exchange(
+                    head,
+                    head + self.frame_len,
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                )
+                .is_ok()
+            {
+                return true;
+            }
+        }
+    }
+
+    /// Like [`AudioRing::pop_frame`], but waits up to `timeout` for a frame from a thread that
+    /// is not running on the async runtime.
+    pub(crate) fn pop_frame_blocking(&self, out: &mut [f32], timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if self.pop_frame(out) {
+                return true;
+            }
+            if self.is_closed() || Instant::now() >= deadline {
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    /// Resolves once a frame is queued or the ring has been closed.
+    pub(crate) async fn readable(&self) {
+        if self.queued_frames() > 0 || self.is_closed() {
+            return;
+        }
+        self.readable.notified().await;
+    }
+
+    pub(crate) fn record_underrun(&self) {
+        self.underruns.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Tells the other side to stop; the producer checks this to find out nobody is reading.
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Ordering::Release);
+        self.readable.notify_one();
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn queued_frames(&self) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        tail.saturating_sub(head) / self.frame_len
+    }
+
+    pub(crate) fn stats(&self) -> AudioRingStats {
+        AudioRingStats {
+            overruns: self.overruns.load(Ordering::Relaxed),
+            underruns: self.underruns.load(Ordering::Relaxed),
+            queued_frames: self.queued_frames(),
+            capacity_frames: self.slots.len() / self.frame_len,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    fn frame(value: f32) -> [f32; 4] {
+        [value; 4]
+    }
+
+    #[test]
+    fn pops_frames_in_push_order() {
+        let ring = AudioRing::new(4, 3);
+        let mut out = [0.0; 4];
+        assert!(!ring.pop_frame(&mut out));
+
+        ring.push_frame(&frame(1.0));
+        ring.push_frame(&frame(2.0));
+        assert!(ring.pop_frame(&mut out));
+        assert_eq!(out, frame(1.0));
+        ring.push_frame(&frame(3.0));
+        assert!(ring.pop_frame(&mut out));
+        assert_eq!(out, frame(2.0));
+        assert!(ring.pop_frame(&mut out));
+        assert_eq!(out, frame(3.0));
+        assert!(!ring.pop_frame(&mut out));
+    }
+
+    #[test]
+    fn overrun_drops_the_oldest_frame() {
+        let ring = AudioRing::new(4, 3);
+        for value in 1..=5 {
+            ring.push_frame(&frame(value as f32));
+        }
+        let stats = ring.stats();
+        assert_eq!(stats.overruns, 2);
+        assert_eq!(stats.queued_frames, 3);
+        assert_eq!(stats.capacity_frames, 3);
+
+        let mut out = [0.0; 4];
+        for value in 3..=5 {
+            assert!(ring.pop_frame(&mut out));
+            assert_eq!(out, frame(value as f32));
+        }
+        assert!(!ring.pop_frame(&mut out));
+    }
+
+    #[test]
+    fn counts_queued_frames() {
+        let ring = AudioRing::new(4, 8);
+        let mut out = [0.0; 4];
+        assert_eq!(ring.queued_frames(), 0);
+        for expected in 1..=3 {
+            ring.push_frame(&frame(0.5));
+            assert_eq!(ring.queued_frames(), expected);
+        }
+        ring.pop_frame(&mut out);
+        assert_eq!(ring.queued_frames(), 2);
+        for _ in 0..20 {
+            ring.push_frame(&frame(0.5));
+        }
+        assert_eq!(ring.queued_frames(), 8);
+    }
+
+    #[test]
+    fn close_ends_a_blocking_pop_once_drained() {
+        let ring = AudioRing::new(4, 3);
+        let mut out = [0.0; 4];
+        ring.push_frame(&frame(1.0));
+        ring.close();
+        assert!(ring.is_closed());
+
+        // Frames queued before closing are still handed out.
+        assert!(ring.pop_frame_blocking(&mut out, Duration::from_secs(5)));
+        assert_eq!(out, frame(1.0));
+        let started = Instant::now();
+        assert!(!ring.pop_frame_blocking(&mut out, Duration::from_secs(5)));
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn producer_and_consumer_on_separate_threads() {
+        const FRAMES: usize = 200_000;
+        let ring = Arc::new(AudioRing::new(4, 16));
+        let producer_ring = Arc::clone(&ring);
+        let producer = std::thread::spawn(move || {
+            for index in 0..FRAMES {
+                producer_ring.push_frame(&frame(index as f32));
+            }
+            producer_ring.close();
+        });
+
+        let mut out = [0.0; 4];
+        let mut received = 0usize;
+        let mut last = -1.0f32;
+        while ring.pop_frame_blocking(&mut out, Duration::from_secs(5)) {
+            // A frame is never torn between two pushes, and order is kept across drops.
+            assert!(
+                out.iter().all(|sample| *sample == out[0]),
+                "torn frame {out:?}"
+            );
+            assert!(out[0] > last, "{} after {last}", out[0]);
+            last = out[0];
+            received += 1;
+        }
+        producer.join().unwrap();
+
+        assert_eq!(last, (FRAMES - 1) as f32);
+        assert_eq!(received as u64 + ring.stats().overruns, FRAMES as u64);
+    }
+}
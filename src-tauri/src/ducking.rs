@@ -0,0 +1,277 @@
+//! Turns shared and played-back audio down while someone in the call is talking. The microphone
+//! gate and the remote voice feeds report activity; each audio path that should give way runs a
+//! [`Ducker`] that follows it with its own gain envelope.
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::voice_processing::SAMPLE_RATE;
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DuckingSettings {
+    pub enabled: bool,
+    /// How far ducked audio is turned down.
+    pub depth_db: f32,
+    /// Level a remote voice has to reach to count as speaking.
+    pub threshold_db: f32,
+    pub attack_ms: f32,
+    pub release_ms: f32,
+    /// How long ducking holds after the last voice frame, which bridges gaps between words.
+    pub hold_ms: f32,
+    /// Also duck other people's shared audio in native playback, not only our own share.
+    pub duck_playback: bool,
+}
+
+impl Default for DuckingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            depth_db: 12.0,
+            threshold_db: -45.0,
+            attack_ms: 30.0,
+            release_ms: 400.0,
+            hold_ms: 250.0,
+            duck_playback: false,
+        }
+    }
+}
+
+/// Voice activity shared between the reporters and the duckers.
+pub(crate) struct Ducking {
+    settings: Mutex<DuckingSettings>,
+    epoch: Instant,
+    /// Milliseconds after `epoch` at which someone was last heard talking, 0 for never.
+    last_voice_ms: AtomicU64,
+}
+
+impl Default for Ducking {
+    fn default() -> Self {
+        Self {
+            settings: Mutex::new(DuckingSettings::default()),
+            epoch: Instant::now(),
+            last_voice_ms: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Ducking {
+    pub(crate) fn configure(&self, settings: DuckingSettings) {
+        if let Ok(mut current) = self.settings.lock() {
+            *current = settings;
+        }
+    }
+
+    fn settings(&self) -> DuckingSettings {
+        self.settings
+            .lock()
+            .map(|settings| settings.clone())
+            .unwrap_or_default()
+    }
+
+    fn now_ms(&self) -> u64 {
+        self.epoch.elapsed().as_millis() as u64 + 1
+    }
+
+    /// Called for every microphone frame that passes the gate.
+    pub(crate) fn report_voice(&self) {
+        self.last_voice_ms.store(self.now_ms(), Ordering::Relaxed);
+    }
+
+    /// Called with every frame of a remote voice track; counts as talking above the threshold.
+    pub(crate) fn report_remote_frame(&self, samples: &[f32]) {
+        if samples.is_empty() {
+            return;
+        }
+        let threshold_db = self
+            .settings
+            .lock()
+            .map(|settings| settings.threshold_db)
+            .unwrap_or(DuckingSettings::default().threshold_db);
+        let mean_square =
+            samples.iter().map(|sample| sample * sample).sum::<f32>() / samples.len() as f32;
+        if 10.0 * mean_square.max(1e-12).log10() >= threshold_db {
+            self.report_voice();
+        }
+    }
+
+    fn voice_active(&self, hold_ms: f32) -> bool {
+        let last = self.last_voice_ms.load(Ordering::Relaxed);
+        last != 0 && self.now_ms().saturating_sub(last) <= hold_ms.max(0.0) as u64
+    }
+}
+
+/// Smoothed gain for one audio path.
+pub(crate) struct Ducker {
+    ducking: Arc<Ducking>,
+    /// Whether this path is other people's audio, which only ducks with `duck_playback`.
+    playback: bool,
+    gain: f32,
+    gains: Vec<f32>,
+}
+
+impl Ducker {
+    pub(crate) fn new(ducking: Arc<Ducking>, playback: bool) -> Self {
+        Self {
+            ducking,
+            playback,
+            gain: 1.0,
+            gains: Vec::new(),
+        }
+    }
+
+    /// The gain for each of the next `frames` sample frames, moving towards the ducked or the
+    /// full level with the attack or release time.
+    pub(crate) fn next_gains(&mut self, frames: usize) -> &[f32] {
+        let settings = self.ducking.settings();
+        let applies = settings.enabled && (!self.playback || settings.duck_playback);
+        let target = if applies && self.ducking.voice_active(settings.hold_ms) {
+            10.0f32.powf(-settings.depth_db.clamp(0.0, 60.0) / 20.0)
+        } else {
+            1.0
+        };
+        let time_ms = if target < self.gain {
+            settings.attack_ms
+        } else {
+            settings.release_ms
+        };
+        let coeff = (-1000.0 / (time_ms.max(1.0) * SAMPLE_RATE as f32)).exp();
+        self.gains.resize(frames, 1.0);
+        for gain in self.gains.iter_mut() {
+            self.gain = target + (self.gain - target) * coeff;
+            *gain = self.gain;
+        }
+        &self.gains
+    }
+
+    /// Applies the gain envelope to interleaved `samples` in place.
+    pub(crate) fn process(&mut self, samples: &mut [f32], channels: usize) {
+        let channels = channels.max(1);
+        let gains = self.next_gains(samples.len() / channels);
+        for (frame, gain) in samples.chunks_exact_mut(channels).zip(gains.iter()) {
+            frame.iter_mut().for_each(|sample| *sample *= gain);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn ducking(settings: DuckingSettings) -> Arc<Ducking> {
+        let ducking = Ducking {
+            // Far enough back that a voice can be reported in the past.
+            epoch: Instant::now() - Duration::from_secs(10),
+            ..Ducking::default()
+        };
+        ducking.configure(DuckingSettings {
+            enabled: true,
+            ..settings
+        });
+        Arc::new(ducking)
+    }
+
+    fn voice_heard_ms_ago(ducking: &Ducking, ms: u64) {
+        ducking
+            .last_voice_ms
+            .store(ducking.now_ms() - ms, Ordering::Relaxed);
+    }
+
+    fn samples(ms: f32) -> usize {
+        (ms * SAMPLE_RATE as f32 / 1000.0) as usize
+    }
+
+    #[test]
+    fn voice_ducks_with_the_attack_time() {
+        let settings = DuckingSettings::default();
+        let ducking = ducking(settings.clone());
+        let mut ducker = Ducker::new(Arc::clone(&ducking), false);
+        let ducked = 10f32.powf(-settings.depth_db / 20.0);
+        ducking.report_voice();
+
+        let gains = ducker
+            .next_gains(samples(settings.attack_ms * 5.0))
+            .to_vec();
+
+        assert!(gains.windows(2).all(|pair| pair[1] <= pair[0]));
+        // One time constant in, the gain has covered 1 - 1/e of the way down.
+        let one_attack = gains[samples(settings.attack_ms) - 1];
+        let expected = ducked + (1.0 - ducked) * (-1.0f32).exp();
+        assert!(
+            (one_attack - expected).abs() < 0.01,
+            "{one_attack} vs {expected}"
+        );
+        assert!((gains.last().unwrap() - ducked).abs() < 0.01);
+    }
+
+    #[test]
+    fn silence_releases_with_the_release_time() {
+        let settings = DuckingSettings::default();
+        let ducking = ducking(settings.clone());
+        let mut ducker = Ducker::new(Arc::clone(&ducking), false);
+        let ducked = 10f32.powf(-settings.depth_db / 20.0);
+        ducking.report_voice();
+        ducker.next_gains(samples(settings.attack_ms * 10.0));
+        voice_heard_ms_ago(&ducking, 10_000);
+
+        let gains = ducker
+            .next_gains(samples(settings.release_ms * 5.0))
+            .to_vec();
+
+        assert!(gains.windows(2).all(|pair| pair[1] >= pair[0]));
+        let one_release = gains[samples(settings.release_ms) - 1];
+        let expected = 1.0 - (1.0 - ducked) * (-1.0f32).exp();
+        assert!(
+            (one_release - expected).abs() < 0.01,
+            "{one_release} vs {expected}"
+        );
+        assert!(*gains.last().unwrap() > 0.99);
+    }
+
+    #[test]
+    fn ducking_holds_between_words() {
+        let settings = DuckingSettings::default();
+        let ducking = ducking(settings.clone());
+        let hold_ms = settings.hold_ms as u64;
+
+        voice_heard_ms_ago(&ducking, hold_ms - 100);
+        assert!(ducking.voice_active(settings.hold_ms));
+        voice_heard_ms_ago(&ducking, hold_ms + 100);
+        assert!(!ducking.voice_active(settings.hold_ms));
+    }
+
+    #[test]
+    fn playback_ducks_only_with_duck_playback() {
+        let duck_target = |settings: DuckingSettings, playback: bool| {
+            let ducking = ducking(settings);
+            let mut ducker = Ducker::new(Arc::clone(&ducking), playback);
+            ducking.report_voice();
+            *ducker.next_gains(SAMPLE_RATE as usize).last().unwrap()
+        };
+
+        assert!(duck_target(DuckingSettings::default(), false) < 0.3);
+        assert_eq!(duck_target(DuckingSettings::default(), true), 1.0);
+        let duck_playback = DuckingSettings {
+            duck_playback: true,
+            ..DuckingSettings::default()
+        };
+        assert!(duck_target(duck_playback, true) < 0.3);
+    }
+
+    #[test]
+    fn remote_frames_count_as_voice_above_the_threshold() {
+        let ducking = ducking(DuckingSettings::default());
+
+        ducking.report_remote_frame(&[0.001; 480]);
+        assert!(!ducking.voice_active(1_000.0));
+        ducking.report_remote_frame(&[0.1; 480]);
+        assert!(ducking.voice_active(1_000.0));
+    }
+}
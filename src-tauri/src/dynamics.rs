@@ -0,0 +1,406 @@
+//! Tone and level shaping for the native microphone: a low cut and presence EQ, a compressor
+//! and a limiter, run in that order after the gate. Settings are plain data so the frontend can
+//! keep them as presets and swap them on a running session.
+
+use std::f32::consts::{FRAC_1_SQRT_2, PI};
+
+use serde::{Deserialize, Serialize};
+
+use crate::voice_processing::SAMPLE_RATE;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DynamicsSettings {
+    pub eq: EqSettings,
+    pub compressor: CompressorSettings,
+    pub limiter: LimiterSettings,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EqSettings {
+    pub low_cut_enabled: bool,
+    pub low_cut_hz: f32,
+    pub presence_enabled: bool,
+    pub presence_hz: f32,
+    pub presence_gain_db: f32,
+    pub presence_q: f32,
+}
+
+impl Default for EqSettings {
+    fn default() -> Self {
+        Self {
+            low_cut_enabled: false,
+            low_cut_hz: 80.0,
+            presence_enabled: false,
+            presence_hz: 3_500.0,
+            presence_gain_db: 3.0,
+            presence_q: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CompressorSettings {
+    pub enabled: bool,
+    pub threshold_db: f32,
+    pub ratio: f32,
+    pub attack_ms: f32,
+    pub release_ms: f32,
+    pub makeup_db: f32,
+}
+
+impl Default for CompressorSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold_db: -20.0,
+            ratio: 3.0,
+            attack_ms: 5.0,
+            release_ms: 120.0,
+            makeup_db: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LimiterSettings {
+    pub enabled: bool,
+    pub ceiling_db: f32,
+    pub release_ms: f32,
+}
+
+impl Default for LimiterSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ceiling_db: -1.0,
+            release_ms: 50.0,
+        }
+    }
+}
+
+/// RBJ cookbook biquad in transposed direct form II, one state per channel.
+#[derive(Clone, Copy)]
+struct Biquad {
+    b0: f32,
+    b1: f32,
+    b2: f32,
+    a1: f32,
+    a2: f32,
+}
+
+impl Biquad {
+    fn high_pass(frequency: f32, q: f32) -> Self {
+        let (cos, alpha) = Self::prewarp(frequency, q);
+        let a0 = 1.0 + alpha;
+        Self {
+            b0: (1.0 + cos) / 2.0 / a0,
+            b1: -(1.0 + cos) / a0,
+            b2: (1.0 + cos) / 2.0 / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha) / a0,
+        }
+    }
+
+    fn peaking(frequency: f32, q: f32, gain_db: f32) -> Self {
+        let (cos, alpha) = Self::prewarp(frequency, q);
+        let amplitude = 10f32.powf(gain_db / 40.0);
+        let a0 = 1.0 + alpha / amplitude;
+        Self {
+            b0: (1.0 + alpha * amplitude) / a0,
+            b1: -2.0 * cos / a0,
+            b2: (1.0 - alpha * amplitude) / a0,
+            a1: -2.0 * cos / a0,
+            a2: (1.0 - alpha / amplitude) / a0,
+        }
+    }
+
+    fn prewarp(frequency: f32, q: f32) -> (f32, f32) {
+        let nyquist = SAMPLE_RATE as f32 / 2.0;
+        let omega = 2.0 * PI * frequency.clamp(10.0, nyquist * 0.9) / SAMPLE_RATE as f32;
+        (omega.cos(), omega.sin() / (2.0 * q.max(0.1)))
+    }
+
+    fn process(&self, state: &mut [f32; 2], input: f32) -> f32 {
+        let output = self.b0 * input + state[0];
+        state[0] = self.b1 * input - self.a1 * output + state[1];
+        state[1] = self.b2 * input - self.a2 * output;
+        output
+    }
+}
+
+fn time_coefficient(ms: f32) -> f32 {
+    let samples = (ms.max(0.1) * SAMPLE_RATE as f32 / 1000.0).max(1.0);
+    (-1.0 / samples).exp()
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10f32.powf(db / 20.0)
+}
+
+/// Runs the EQ, compressor and limiter over planar frames. Filter state survives
+/// reconfiguration, so presets can be changed mid-sentence without clicks.
+#[derive(Default)]
+pub struct DynamicsProcessor {
+    settings: DynamicsSettings,
+    low_cut: Option<Biquad>,
+    presence: Option<Biquad>,
+    /// Per channel: low cut state, then presence state.
+    filter_state: Vec<[[f32; 2]; 2]>,
+    compressor_envelope: f32,
+    compressor_attack: f32,
+    compressor_release: f32,
+    limiter_gain: f32,
+    limiter_ceiling: f32,
+    limiter_release: f32,
+}
+
+impl DynamicsProcessor {
+    pub fn new(settings: &DynamicsSettings) -> Self {
+        let mut processor = Self {
+            limiter_gain: 1.0,
+            ..Self::default()
+        };
+        processor.configure(settings);
+        processor
+    }
+
+    pub fn configure(&mut self, settings: &DynamicsSettings) {
+        self.settings = settings.clone();
+        let eq = &settings.eq;
+        self.low_cut = eq
+            .low_cut_enabled
+            .then(|| Biquad::high_pass(eq.low_cut_hz, FRAC_1_SQRT_2));
+        self.presence = eq.presence_enabled.then(|| {
+            Biquad::peaking(
+                eq.presence_hz,
+                eq.presence_q,
+                eq.presence_gain_db.clamp(-12.0, 12.0),
+            )
+        });
+        self.compressor_attack = time_coefficient(settings.compressor.attack_ms);
+        self.compressor_release = time_coefficient(settings.compressor.release_ms);
+        self.limiter_ceiling = db_to_gain(settings.limiter.ceiling_db.clamp(-24.0, 0.0));
+        self.limiter_release = time_coefficient(settings.limiter.release_ms);
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.low_cut.is_some()
+            || self.presence.is_some()
+            || self.settings.compressor.enabled
+            || self.settings.limiter.enabled
+    }
+
+    pub fn process(&mut self, channels: &mut [&mut [f32]]) {
+        if !self.is_active() {
+            return;
+        }
+        self.filter_state.resize(channels.len(), [[0.0; 2]; 2]);
+        for (channel, state) in channels.iter_mut().zip(self.filter_state.iter_mut()) {
+            for sample in channel.iter_mut() {
+                if let Some(filter) = &self.low_cut {
+                    *sample = filter.process(&mut state[0], *sample);
+                }
+                if let Some(filter) = &self.presence {
+                    *sample = filter.process(&mut state[1], *sample);
+                }
+            }
+        }
+
+        let compressor = &self.settings.compressor;
+        let limiter_enabled = self.settings.limiter.enabled;
+        if !compressor.enabled && !limiter_enabled {
+            return;
+        }
+        let threshold_db = compressor.threshold_db.clamp(-60.0, 0.0);
+        let slope = 1.0 - 1.0 / compressor.ratio.clamp(1.0, 20.0);
+        let makeup = db_to_gain(compressor.makeup_db.clamp(0.0, 24.0));
+        let frame_len = channels
+            .iter()
+            .map(|channel| channel.len())
+            .max()
+            .unwrap_or(0);
+        for index in 0..frame_len {
+            // Channels share one detector so the stereo image does not wander.
+            let level = channels
+                .iter()
+                .filter_map(|channel| channel.get(index))
+                .fold(0.0f32, |level, sample| level.max(sample.abs()));
+
+            let mut gain = 1.0;
+            if compressor.enabled {
+                let coefficient = if level > self.compressor_envelope {
+                    self.compressor_attack
+                } else {
+                    self.compressor_release
+                };
+                self.compressor_envelope =
+                    coefficient * self.compressor_envelope + (1.0 - coefficient) * level;
+                let envelope_db = 20.0 * self.compressor_envelope.max(1e-6).log10();
+                let over_db = (envelope_db - threshold_db).max(0.0);
+                gain = db_to_gain(-over_db * slope) * makeup;
+            }
+
+            if limiter_enabled {
+                // Instant attack, smooth release, and a hard clamp on top so nothing above the
+                // ceiling ever gets through.
+                let peak = level * gain;
+                let needed = if peak > self.limiter_ceiling {
+                    self.limiter_ceiling / peak
+                } else {
+                    1.0
+                };
+                self.limiter_gain = if needed < self.limiter_gain {
+                    needed
+                } else {
+                    self.limiter_release * self.limiter_gain + (1.0 - self.limiter_release) * needed
+                };
+                gain *= self.limiter_gain;
+            }
+
+            for channel in channels.iter_mut() {
+                if let Some(sample) = channel.get_mut(index) {
+                    *sample *= gain;
+                    if limiter_enabled {
+                        *sample = sample.clamp(-self.limiter_ceiling, self.limiter_ceiling);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(frequency: f32, amplitude: f32, seconds: f32) -> Vec<f32> {
+        (0..(seconds * SAMPLE_RATE as f32) as usize)
+            .map(|index| {
+                (index as f32 * frequency / SAMPLE_RATE as f32 * 2.0 * PI).sin() * amplitude
+            })
+            .collect()
+    }
+
+    /// Runs `samples` through the processor in 10 ms mono frames.
+    fn run(processor: &mut DynamicsProcessor, samples: &mut [f32]) {
+        for frame in samples.chunks_mut(SAMPLE_RATE as usize / 100) {
+            processor.process(&mut [frame]);
+        }
+    }
+
+    fn peak_db(samples: &[f32]) -> f32 {
+        20.0 * samples
+            .iter()
+            .fold(0.0f32, |peak, sample| peak.max(sample.abs()))
+            .log10()
+    }
+
+    #[test]
+    fn limiter_holds_a_full_scale_burst_under_the_ceiling() {
+        let settings = DynamicsSettings {
+            compressor: CompressorSettings {
+                enabled: true,
+                makeup_db: 12.0,
+                ..CompressorSettings::default()
+            },
+            limiter: LimiterSettings {
+                enabled: true,
+                ceiling_db: -6.0,
+                ..LimiterSettings::default()
+            },
+            ..DynamicsSettings::default()
+        };
+        let mut processor = DynamicsProcessor::new(&settings);
+        let mut samples = vec![0.0; SAMPLE_RATE as usize / 10];
+        samples.extend(
+            (0..SAMPLE_RATE as usize / 10).map(|index| if index % 2 == 0 { 1.0 } else { -1.0 }),
+        );
+        samples.extend(sine(1_000.0, 1.0, 0.2));
+
+        run(&mut processor, &mut samples);
+
+        let ceiling = db_to_gain(-6.0);
+        assert!(samples.iter().all(|sample| sample.abs() <= ceiling + 1e-6));
+        assert!(
+            peak_db(&samples) > -6.5,
+            "limited too far: {} dB",
+            peak_db(&samples)
+        );
+    }
+
+    #[test]
+    fn compressor_cuts_the_overshoot_by_the_ratio() {
+        let settings = DynamicsSettings {
+            compressor: CompressorSettings {
+                enabled: true,
+                threshold_db: -20.0,
+                ratio: 4.0,
+                ..CompressorSettings::default()
+            },
+            ..DynamicsSettings::default()
+        };
+        let mut processor = DynamicsProcessor::new(&settings);
+        // A -6 dBFS tone is 14 dB over the threshold; at 4:1 it should come out 10.5 dB lower.
+        let mut samples = sine(1_000.0, 0.5, 1.0);
+
+        run(&mut processor, &mut samples);
+
+        let settled = &samples[samples.len() / 2..];
+        let cut = -6.0 - peak_db(settled);
+        assert!((cut - 14.0 * (1.0 - 1.0 / 4.0)).abs() < 1.0, "cut {cut} dB");
+    }
+
+    #[test]
+    fn compressor_leaves_a_tone_below_the_threshold_alone() {
+        let settings = DynamicsSettings {
+            compressor: CompressorSettings {
+                enabled: true,
+                threshold_db: -20.0,
+                ..CompressorSettings::default()
+            },
+            ..DynamicsSettings::default()
+        };
+        let mut processor = DynamicsProcessor::new(&settings);
+        let input = sine(1_000.0, 0.05, 0.5);
+        let mut samples = input.clone();
+
+        run(&mut processor, &mut samples);
+
+        assert!(samples
+            .iter()
+            .zip(&input)
+            .all(|(output, input)| (output - input).abs() < 1e-6));
+    }
+
+    #[test]
+    fn low_cut_removes_rumble_and_keeps_voice() {
+        let settings = DynamicsSettings {
+            eq: EqSettings {
+                low_cut_enabled: true,
+                ..EqSettings::default()
+            },
+            ..DynamicsSettings::default()
+        };
+        let level_after = |frequency: f32| {
+            let mut processor = DynamicsProcessor::new(&settings);
+            let mut samples = sine(frequency, 0.5, 1.0);
+            run(&mut processor, &mut samples);
+            peak_db(&samples[samples.len() / 2..]) - peak_db(&sine(frequency, 0.5, 1.0))
+        };
+
+        assert!(
+            level_after(20.0) < -20.0,
+            "20 Hz at {} dB",
+            level_after(20.0)
+        );
+        assert!(
+            level_after(1_000.0).abs() < 0.5,
+            "1 kHz at {} dB",
+            level_after(1_000.0)
+        );
+    }
+}
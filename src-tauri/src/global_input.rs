@@ -0,0 +1,352 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Mutex,
+};
+use std::time::{Duration, Instant};
+
+use rdev::{Button, EventType, Key};
+
+/// A key or mouse button the user bound to a global action, parsed from the
+/// `KeyboardEvent.code` style strings the frontend stores (`"KeyV"`, `"F13"`, `"Mouse4"`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum InputBinding {
+    Key(Key),
+    Button(Button),
+}
+
+impl InputBinding {
+    pub(crate) fn parse(code: &str) -> Option<Self> {
+        if let Some(button) = mouse_button_for_code(code) {
+            return Some(Self::Button(button));
+        }
+        key_for_code(code).map(Self::Key)
+    }
+
+    /// `Some(true)` when `event` presses this binding, `Some(false)` when it releases it.
+    pub(crate) fn transition(&self, event: &EventType) -> Option<bool> {
+        match (self, event) {
+            (Self::Key(bound), EventType::KeyPress(key)) if bound == key => Some(true),
+            (Self::Key(bound), EventType::KeyRelease(key)) if bound == key => Some(false),
+            (Self::Button(bound), EventType::ButtonPress(button)) if bound == button => Some(true),
+            (Self::Button(bound), EventType::ButtonRelease(button)) if bound == button => {
+                Some(false)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// When keys last went down, fed from the input hook and read by the audio thread once per
+/// frame. Auto-repeat of a held key does not count as a new press.
+pub(crate) struct KeyboardActivity {
+    epoch: Instant,
+    held: Mutex<Vec<Key>>,
+    /// Milliseconds since `epoch` of the last two presses, offset by one so zero means never.
+    last_press_ms: AtomicU64,
+    previous_press_ms: AtomicU64,
+}
+
+impl Default for KeyboardActivity {
+    fn default() -> Self {
+        Self {
+            epoch: Instant::now(),
+            held: Mutex::new(Vec::new()),
+            last_press_ms: AtomicU64::new(0),
+            previous_press_ms: AtomicU64::new(0),
+        }
+    }
+}
+
+impl KeyboardActivity {
+    pub(crate) fn record(&self, event: &EventType) {
+        let Ok(mut held) = self.held.lock() else {
+            return;
+        };
+        match event {
+            EventType::KeyPress(key) => {
+                if held.contains(key) {
+                    return;
+                }
+                held.push(*key);
+                let now = self.epoch.elapsed().as_millis() as u64 + 1;
+                let last = self.last_press_ms.swap(now, Ordering::Relaxed);
+                self.previous_press_ms.store(last, Ordering::Relaxed);
+            }
+            EventType::KeyRelease(key) => held.retain(|held_key| held_key != key),
+            _ => {}
+        }
+    }
+
+    /// Forgets held keys and past presses, for when nothing is watching any more.
+    pub(crate) fn clear(&self) {
+        if let Ok(mut held) = self.held.lock() {
+            held.clear();
+        }
+        self.last_press_ms.store(0, Ordering::Relaxed);
+        self.previous_press_ms.store(0, Ordering::Relaxed);
+    }
+
+    /// Whether a key went down within the last `window`.
+    pub(crate) fn pressed_within(&self, window: Duration) -> bool {
+        self.since(self.last_press_ms.load(Ordering::Relaxed))
+            .is_some_and(|elapsed| elapsed <= window)
+    }
+
+    /// Whether the user is typing: a key went down within the last `window` and the press
+    /// before it came no more than `gap` earlier.
+    pub(crate) fn typing_within(&self, window: Duration, gap: Duration) -> bool {
+        let last = self.last_press_ms.load(Ordering::Relaxed);
+        let previous = self.previous_press_ms.load(Ordering::Relaxed);
+        previous != 0
+            && last.saturating_sub(previous) <= gap.as_millis() as u64
+            && self.pressed_within(window)
+    }
+
+    fn since(&self, press_ms: u64) -> Option<Duration> {
+        (press_ms != 0).then(|| {
+            self.epoch
+                .elapsed()
+                .saturating_sub(Duration::from_millis(press_ms - 1))
+        })
+    }
+}
+
+/// Starts the OS-wide input hook on its own thread. The hook sees input regardless of which
+/// window has focus; `rdev::listen` never returns while it is working.
+pub(crate) fn spawn_listener<F>(mut callback: F) -> Result<(), String>
+where
+    F: FnMut(&EventType) + Send + 'static,
+{
+    std::thread::Builder::new()
+        .name("global-input-hook".to_string())
+        .spawn(move || {
+            let _ = rdev::listen(move |event| callback(&event.event_type));
+        })
+        .map(|_| ())
+        .map_err(|err| err.to_string())
+}
+
+fn mouse_button_for_code(code: &str) -> Option<Button> {
+    // Side buttons are reported as raw codes that differ per platform.
+    #[cfg(target_os = "windows")]
+    const SIDE_BUTTONS: (u8, u8) = (1, 2);
+    #[cfg(not(target_os = "windows"))]
+    const SIDE_BUTTONS: (u8, u8) = (8, 9);
+
+    match code {
+        "Mouse3" => Some(Button::Middle),
+        "Mouse4" => Some(Button::Unknown(SIDE_BUTTONS.0)),
+        "Mouse5" => Some(Button::Unknown(SIDE_BUTTONS.1)),
+        _ => None,
+    }
+}
+
+fn key_for_code(code: &str) -> Option<Key> {
+    let key = match code {
+        "AltLeft" => Key::Alt,
+        "AltRight" => Key::AltGr,
+        "Backspace" => Key::Backspace,
+        "CapsLock" => Key::CapsLock,
+        "ControlLeft" => Key::ControlLeft,
+        "ControlRight" => Key::ControlRight,
+        "Delete" => Key::Delete,
+        "ArrowDown" => Key::DownArrow,
+        "ArrowUp" => Key::UpArrow,
+        "ArrowLeft" => Key::LeftArrow,
+        "ArrowRight" => Key::RightArrow,
+        "End" => Key::End,
+        "Escape" => Key::Escape,
+        "Home" => Key::Home,
+        "MetaLeft" => Key::MetaLeft,
+        "MetaRight" => Key::MetaRight,
+        "PageDown" => Key::PageDown,
+        "PageUp" => Key::PageUp,
+        "Enter" => Key::Return,
+        "ShiftLeft" => Key::ShiftLeft,
+        "ShiftRight" => Key::ShiftRight,
+        "Space" => Key::Space,
+        "Tab" => Key::Tab,
+        "PrintScreen" => Key::PrintScreen,
+        "ScrollLock" => Key::ScrollLock,
+        "Pause" => Key::Pause,
+        "NumLock" => Key::NumLock,
+        "Backquote" => Key::BackQuote,
+        "Minus" => Key::Minus,
+        "Equal" => Key::Equal,
+        "BracketLeft" => Key::LeftBracket,
+        "BracketRight" => Key::RightBracket,
+        "Semicolon" => Key::SemiColon,
+        "Quote" => Key::Quote,
+        "Backslash" => Key::BackSlash,
+        "IntlBackslash" => Key::IntlBackslash,
+        "Comma" => Key::Comma,
+        "Period" => Key::Dot,
+        "Slash" => Key::Slash,
+        "Insert" => Key::Insert,
+        "NumpadEnter" => Key::KpReturn,
+        "NumpadSubtract" => Key::KpMinus,
+        "NumpadAdd" => Key::KpPlus,
+        "NumpadMultiply" => Key::KpMultiply,
+        "NumpadDivide" => Key::KpDivide,
+        "NumpadDecimal" => Key::KpDelete,
+        "F1" => Key::F1,
+        "F2" => Key::F2,
+        "F3" => Key::F3,
+        "F4" => Key::F4,
+        "F5" => Key::F5,
+        "F6" => Key::F6,
+        "F7" => Key::F7,
+        "F8" => Key::F8,
+        "F9" => Key::F9,
+        "F10" => Key::F10,
+        "F11" => Key::F11,
+        "F12" => Key::F12,
+        _ => return key_for_indexed_code(code),
+    };
+    Some(key)
+}
+
+fn key_for_indexed_code(code: &str) -> Option<Key> {
+    if let Some(letter) = code.strip_prefix("Key") {
+        let key = match letter {
+            "A" => Key::KeyA,
+            "B" => Key::KeyB,
+            "C" => Key::KeyC,
+            "D" => Key::KeyD,
+            "E" => Key::KeyE,
+            "F" => Key::KeyF,
+            "G" => Key::KeyG,
+            "H" => Key::KeyH,
+            "I" => Key::KeyI,
+            "J" => Key::KeyJ,
+            "K" => Key::KeyK,
+            "L" => Key::KeyL,
+            "M" => Key::KeyM,
+            "N" => Key::KeyN,
+            "O" => Key::KeyO,
+            "P" => Key::KeyP,
+            "Q" => Key::KeyQ,
+            "R" => Key::KeyR,
+            "S" => Key::KeyS,
+            "T" => Key::KeyT,
+            "U" => Key::KeyU,
+            "V" => Key::KeyV,
+            "W" => Key::KeyW,
+            "X" => Key::KeyX,
+            "Y" => Key::KeyY,
+            "Z" => Key::KeyZ,
+            _ => return None,
+        };
+        return Some(key);
+    }
+
+    let (digit, numpad) = if let Some(digit) = code.strip_prefix("Digit") {
+        (digit, false)
+    } else if let Some(digit) = code.strip_prefix("Numpad") {
+        (digit, true)
+    } else {
+        return None;
+    };
+    let key = match (digit, numpad) {
+        ("0", false) => Key::Num0,
+        ("1", false) => Key::Num1,
+        ("2", false) => Key::Num2,
+        ("3", false) => Key::Num3,
+        ("4", false) => Key::Num4,
+        ("5", false) => Key::Num5,
+        ("6", false) => Key::Num6,
+        ("7", false) => Key::Num7,
+        ("8", false) => Key::Num8,
+        ("9", false) => Key::Num9,
+        ("0", true) => Key::Kp0,
+        ("1", true) => Key::Kp1,
+        ("2", true) => Key::Kp2,
+        ("3", true) => Key::Kp3,
+        ("4", true) => Key::Kp4,
+        ("5", true) => Key::Kp5,
+        ("6", true) => Key::Kp6,
+        ("7", true) => Key::Kp7,
+        ("8", true) => Key::Kp8,
+        ("9", true) => Key::Kp9,
+        _ => return None,
+    };
+    Some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_mouse_buttons() {
+        assert_eq!(
+            InputBinding::parse("Mouse3"),
+            Some(InputBinding::Button(Button::Middle))
+        );
+        assert!(matches!(
+            InputBinding::parse("Mouse4"),
+            Some(InputBinding::Button(Button::Unknown(_)))
+        ));
+        assert!(matches!(
+            InputBinding::parse("Mouse5"),
+            Some(InputBinding::Button(Button::Unknown(_)))
+        ));
+        assert_ne!(InputBinding::parse("Mouse4"), InputBinding::parse("Mouse5"));
+        assert_eq!(InputBinding::parse("Mouse1"), None);
+        assert_eq!(InputBinding::parse("Mouse2"), None);
+    }
+
+    #[test]
+    fn parses_keyboard_codes() {
+        assert_eq!(
+            InputBinding::parse("KeyV"),
+            Some(InputBinding::Key(Key::KeyV))
+        );
+        assert_eq!(
+            InputBinding::parse("Digit1"),
+            Some(InputBinding::Key(Key::Num1))
+        );
+        assert_eq!(
+            InputBinding::parse("Space"),
+            Some(InputBinding::Key(Key::Space))
+        );
+        assert_eq!(
+            InputBinding::parse("F12"),
+            Some(InputBinding::Key(Key::F12))
+        );
+        assert_eq!(InputBinding::parse("NoSuchKey"), None);
+        assert_eq!(InputBinding::parse(""), None);
+    }
+
+    #[test]
+    fn transition_matches_only_the_bound_input() {
+        let binding = InputBinding::parse("Mouse3").unwrap();
+        assert_eq!(
+            binding.transition(&EventType::ButtonPress(Button::Middle)),
+            Some(true)
+        );
+        assert_eq!(
+            binding.transition(&EventType::ButtonRelease(Button::Middle)),
+            Some(false)
+        );
+        assert_eq!(
+            binding.transition(&EventType::ButtonPress(Button::Left)),
+            None
+        );
+        assert_eq!(binding.transition(&EventType::KeyPress(Key::Space)), None);
+    }
+
+    #[test]
+    fn clear_forgets_recorded_presses() {
+        let activity = KeyboardActivity::default();
+        activity.record(&EventType::KeyPress(Key::KeyA));
+        activity.record(&EventType::KeyRelease(Key::KeyA));
+        activity.record(&EventType::KeyPress(Key::KeyB));
+        let window = Duration::from_secs(60);
+        assert!(activity.typing_within(window, window));
+
+        activity.clear();
+        assert!(!activity.pressed_within(window));
+        assert!(!activity.typing_within(window, window));
+    }
+}
@@ -1,7 +1,8 @@
-use std::collections::VecDeque;
+use std::collections::HashMap;
+use std::path::Path;
 use std::process::Command;
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
+    atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
     mpsc, Arc, Mutex,
 };
 use std::thread::JoinHandle as ThreadJoinHandle;
@@ -9,9 +10,11 @@ use std::time::Duration;
 
 use base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine as _};
 use enigo::{Axis, Button, Coordinate, Direction, Enigo, Key, Keyboard, Mouse, Settings};
+use futures_util::StreamExt;
 use image::{codecs::png::PngEncoder, ColorType, ImageEncoder};
 use libwebrtc::{
     audio_source::native::NativeAudioSource,
+    audio_stream::native::NativeAudioStream,
     desktop_capturer::{
         CaptureSource, DesktopCaptureSourceType, DesktopCapturer, DesktopCapturerOptions,
     },
@@ -23,11 +26,9 @@ use libwebrtc::{
 };
 use livekit::{
     options::{AudioEncoding, TrackPublishOptions, VideoCodec, VideoEncoding},
-    prelude::{LocalTrack, Room, RoomOptions, TrackSource},
-    track::{LocalAudioTrack, LocalVideoTrack},
+    prelude::{LocalTrack, Room, RoomEvent, RoomOptions, TrackSource},
+    track::{LocalAudioTrack, LocalVideoTrack, RemoteAudioTrack, RemoteTrack},
 };
-#[cfg(target_os = "windows")]
-use nnnoiseless::DenoiseState;
 use serde::Deserialize;
 use serde::Serialize;
 #[cfg(target_os = "windows")]
@@ -39,11 +40,29 @@ use tauri::{
     Emitter, Manager, State, Wry,
 };
 use tokio::{sync::watch, task::JoinHandle};
-#[cfg(target_os = "windows")]
-use wasapi::{
-    initialize_mta, DeviceEnumerator, Direction as AudioDirection, SampleType, StreamMode,
-    WaveFormat,
+use webrtc_audio_processing::{Processor as AudioProcessor, Stats as AudioProcessingStats};
+
+pub mod audio_backend;
+mod audio_ring;
+mod ducking;
+pub mod dynamics;
+mod global_input;
+mod playback;
+mod recording;
+mod soundboard;
+pub mod voice_processing;
+
+use audio_backend::{
+    CaptureBackend, CaptureStream, LoopbackTarget, PlaybackBackend, StreamFormat,
+    SAMPLES_PER_FRAME, SAMPLE_RATE,
 };
+use audio_ring::{AudioRing, AudioRingStats};
+use ducking::{Ducker, Ducking, DuckingSettings};
+use global_input::{InputBinding, KeyboardActivity};
+use playback::{PlaybackMixEntry, PlaybackMixer, PlaybackSession, PlaybackSource};
+use recording::{Recorder, RecordingOptions, RecordingSummary, RecordingTap};
+use soundboard::{Soundboard, SoundboardClip, SoundboardMixer};
+use voice_processing::{ChannelMode, VoiceProcessingSettings, VoiceProcessor};
 
 #[derive(Serialize)]
 #[serde(tag = "kind")]
@@ -618,6 +637,43 @@ struct NativeScreenShareStartOptions {
     source: Option<NativeScreenShareSourceSelection>,
     resolution: String,
     fps: u32,
+    /// Leave this app's own playback (the voice channel) out of the shared audio, so viewers
+    /// do not hear the call twice.
+    #[serde(rename = "excludeOwnAudio", default = "default_exclude_own_audio")]
+    exclude_own_audio: bool,
+}
+
+fn default_exclude_own_audio() -> bool {
+    true
+}
+
+/// What a screen share's audio track carries.
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "kebab-case")]
+enum NativeScreenShareAudioMode {
+    /// Only the shared window's application.
+    Application,
+    /// Everything except this app.
+    SystemExcludingSelf,
+    /// The whole system mix.
+    System,
+}
+
+impl From<LoopbackTarget> for NativeScreenShareAudioMode {
+    fn from(target: LoopbackTarget) -> Self {
+        match target {
+            LoopbackTarget::IncludeProcessTree(_) => Self::Application,
+            LoopbackTarget::ExcludeProcessTree(_) => Self::SystemExcludingSelf,
+            LoopbackTarget::System => Self::System,
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct NativeScreenShareStarted {
+    /// `None` when the share has no audio.
+    #[serde(rename = "audioMode")]
+    audio_mode: Option<NativeScreenShareAudioMode>,
 }
 
 #[derive(Clone, Serialize)]
@@ -631,14 +687,136 @@ struct NativeMicrophoneStartOptions {
     #[serde(rename = "livekitUrl")]
     livekit_url: String,
     token: String,
-    #[serde(rename = "deviceId")]
-    device_id: Option<String>,
-    #[serde(rename = "noiseSuppressionMode")]
-    noise_suppression_mode: String,
-    #[serde(rename = "inputSensitivity")]
-    input_sensitivity: f32,
     #[serde(rename = "startMuted")]
     start_muted: bool,
+    #[serde(rename = "qualityProfile", default)]
+    quality_profile: NativeMicrophoneQualityProfile,
+    /// Publish a stereo track even with a voice profile. Mono input is duplicated.
+    #[serde(default)]
+    stereo: bool,
+    #[serde(flatten)]
+    processing: NativeMicrophoneOptions,
+}
+
+/// How the native microphone track is encoded. Fixed for the lifetime of a session, since
+/// the channel count and Opus settings are negotiated when the track is published.
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+enum NativeMicrophoneQualityProfile {
+    /// Low bitrate speech with DTX, cheapest on bandwidth.
+    Voice,
+    #[default]
+    HighQualityVoice,
+    /// Stereo at a high bitrate with the gate and denoiser bypassed, for instruments and
+    /// music.
+    MusicStereo,
+}
+
+impl NativeMicrophoneQualityProfile {
+    fn channels(self) -> u32 {
+        match self {
+            Self::MusicStereo => 2,
+            _ => 1,
+        }
+    }
+
+    fn apply(self, publish_options: &mut TrackPublishOptions) {
+        let (max_bitrate, dtx, red) = match self {
+            Self::Voice => (32_000, true, true),
+            Self::HighQualityVoice => (96_000, false, true),
+            Self::MusicStereo => (160_000, false, false),
+        };
+        publish_options.dtx = dtx;
+        publish_options.red = red;
+        publish_options.audio_encoding = Some(AudioEncoding { max_bitrate });
+    }
+}
+
+/// The part of the native microphone setup that can change while a session is running.
+#[derive(Clone, Deserialize)]
+struct NativeMicrophoneOptions {
+    #[serde(rename = "deviceId")]
+    device_id: Option<String>,
+    #[serde(flatten)]
+    processing: VoiceProcessingSettings,
+}
+
+#[derive(Clone, Deserialize)]
+struct NativeMicrophoneTestOptions {
+    #[serde(rename = "outputDeviceId")]
+    output_device_id: Option<String>,
+    /// Record this many seconds and play them back afterwards instead of monitoring live.
+    #[serde(rename = "recordSeconds")]
+    record_seconds: Option<f32>,
+    #[serde(flatten)]
+    processing: NativeMicrophoneOptions,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NativeMicrophoneLevelPayload {
+    rms: f32,
+    peak: f32,
+    voice_probability: f32,
+    gate_open: bool,
+    speaking: bool,
+    /// See [`voice_processing::FrameDecision::agc_gain_estimate_db`]: the level change across
+    /// the WebRTC stage, not a gain AGC2 reports.
+    agc_gain_estimate_db: Option<f32>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NativeAudioDevicesChangedPayload {
+    devices: Vec<NativeAudioInputDevice>,
+    default_device_id: Option<String>,
+    added: Vec<String>,
+    removed: Vec<String>,
+    default_changed: bool,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NativeMicrophoneDegradedPayload {
+    reason: String,
+    requested_device_id: Option<String>,
+    fallback_device_id: Option<String>,
+    recovered: bool,
+}
+
+#[derive(Clone, Serialize)]
+struct NativeMicrophoneTestStatePayload {
+    phase: &'static str,
+    error: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+struct NativePushToTalkPayload {
+    pressed: bool,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NativeMicrophoneEchoStats {
+    echo_return_loss: Option<f64>,
+    echo_return_loss_enhancement: Option<f64>,
+    divergent_filter_fraction: Option<f64>,
+    residual_echo_likelihood: Option<f64>,
+    delay_ms: Option<u32>,
+    delay_median_ms: Option<u32>,
+}
+
+impl From<AudioProcessingStats> for NativeMicrophoneEchoStats {
+    fn from(stats: AudioProcessingStats) -> Self {
+        Self {
+            echo_return_loss: stats.echo_return_loss,
+            echo_return_loss_enhancement: stats.echo_return_loss_enhancement,
+            divergent_filter_fraction: stats.divergent_filter_fraction,
+            residual_echo_likelihood: stats.residual_echo_likelihood,
+            delay_ms: stats.delay_ms,
+            delay_median_ms: stats.delay_median_ms,
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -722,6 +900,7 @@ struct NativeScreenShareSession {
     capture_task: JoinHandle<()>,
     audio_task: Option<JoinHandle<()>>,
     audio_thread: Option<ThreadJoinHandle<()>>,
+    audio_ring: Option<Arc<AudioRing>>,
     stop_tx: watch::Sender<bool>,
 }
 
@@ -730,13 +909,39 @@ struct NativeScreenShareManager {
     session: Mutex<Option<NativeScreenShareSession>>,
 }
 
+/// Frames buffered between a capture thread and its publisher, 200 ms. Older frames are
+/// dropped beyond that so a stalled publisher costs latency only up to this point.
+const NATIVE_AUDIO_RING_FRAMES: usize = 20;
+
 struct NativeMicrophoneSession {
     room: Room,
     track: LocalAudioTrack,
     audio_task: JoinHandle<()>,
     capture_thread: ThreadJoinHandle<()>,
     stop_tx: watch::Sender<bool>,
+    options_tx: watch::Sender<NativeMicrophoneOptions>,
+    muted: Arc<AtomicBool>,
+    processor: Arc<AudioProcessor>,
+    ring: Arc<AudioRing>,
+    remote_audio_task: JoinHandle<()>,
+}
+
+/// Long-lived pieces shared by the native microphone capture thread across reconfigurations.
+struct NativeMicrophonePipeline {
+    app: tauri::AppHandle,
+    backend: Arc<dyn CaptureBackend>,
+    voice: VoiceProcessor,
     muted: Arc<AtomicBool>,
+    /// Channel count of the frames handed on, 2 for a stereo track.
+    output_channels: usize,
+    /// Capture and process the device's own channels whatever the channel mode says, so the
+    /// music profile's stereo track carries real stereo rather than a duplicated downmix.
+    native_channels: bool,
+    /// Hands the processed audio to a running recording. `None` for the mic test.
+    recording: Option<RecordingTap>,
+    soundboard: Option<SoundboardMixer>,
+    /// Reports the open gate so other audio can duck. `None` for the mic test.
+    ducking: Option<Arc<Ducking>>,
 }
 
 #[derive(Default)]
@@ -744,6 +949,109 @@ struct NativeMicrophoneManager {
     session: Mutex<Option<NativeMicrophoneSession>>,
 }
 
+/// A local hear-yourself loop through the native processing chain, with no room involved.
+struct NativeMicrophoneTestSession {
+    capture_thread: ThreadJoinHandle<()>,
+    playback_thread: ThreadJoinHandle<()>,
+    stop_tx: watch::Sender<bool>,
+    options_tx: watch::Sender<NativeMicrophoneOptions>,
+}
+
+#[derive(Default)]
+struct NativeRecordingManager {
+    recorder: Recorder,
+}
+
+#[derive(Default)]
+struct NativeSoundboardManager {
+    soundboard: Soundboard,
+}
+
+/// Voice activity and settings for ducking, shared by the microphone, the remote feeds, screen
+/// share audio and native playback.
+#[derive(Default)]
+struct NativeDuckingManager {
+    ducking: Arc<Ducking>,
+}
+
+#[derive(Default)]
+struct NativePlaybackManager {
+    mixer: Arc<PlaybackMixer>,
+    session: Mutex<Option<PlaybackSession>>,
+}
+
+#[derive(Default)]
+struct NativeMicrophoneTestManager {
+    session: Mutex<Option<NativeMicrophoneTestSession>>,
+}
+
+#[derive(Default)]
+struct NativePushToTalkManager {
+    binding: Mutex<Option<InputBinding>>,
+    release_delay_ms: AtomicU64,
+    pressed: AtomicBool,
+    release_generation: AtomicU64,
+    /// Manual mute or deafen from the UI. Push-to-talk never opens the microphone over it.
+    ui_muted: AtomicBool,
+}
+
+impl NativePushToTalkManager {
+    /// Muted while the UI mutes, or while push-to-talk is bound and not held.
+    fn microphone_muted(&self) -> bool {
+        if self.ui_muted.load(Ordering::SeqCst) {
+            return true;
+        }
+        let bound = self
+            .binding
+            .lock()
+            .map(|binding| binding.is_some())
+            .unwrap_or(false);
+        bound && !self.pressed.load(Ordering::SeqCst)
+    }
+}
+
+/// The OS-wide input hook can only be installed once per process, so push-to-talk and the
+/// keyboard monitor share it. It is installed the first time either needs it; rdev cannot remove
+/// it again, so once nothing needs it the hook drops every event instead.
+#[derive(Default)]
+struct GlobalInputManager {
+    hook_started: AtomicBool,
+    keyboard: Arc<KeyboardActivity>,
+    /// Running [`KeyboardMonitor`]s. Key presses are only recorded while there is one.
+    keyboard_monitors: AtomicUsize,
+}
+
+/// Keeps key presses flowing into [`GlobalInputManager::keyboard`] for typing suppression
+/// while held.
+struct KeyboardMonitor {
+    app: tauri::AppHandle,
+    activity: Arc<KeyboardActivity>,
+}
+
+impl KeyboardMonitor {
+    fn start(app: &tauri::AppHandle) -> Result<Self, String> {
+        let input = app.state::<GlobalInputManager>();
+        input.keyboard_monitors.fetch_add(1, Ordering::SeqCst);
+        if let Err(err) = start_global_input_hook(app) {
+            input.keyboard_monitors.fetch_sub(1, Ordering::SeqCst);
+            return Err(err);
+        }
+        Ok(Self {
+            app: app.clone(),
+            activity: Arc::clone(&input.keyboard),
+        })
+    }
+}
+
+impl Drop for KeyboardMonitor {
+    fn drop(&mut self) {
+        let input = self.app.state::<GlobalInputManager>();
+        if input.keyboard_monitors.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.activity.clear();
+        }
+    }
+}
+
 fn clamp_byte(value: i32) -> u8 {
     value.clamp(0, 255) as u8
 }
@@ -914,61 +1222,188 @@ fn native_screenshare_bitrate(preset: &str, fps: u32) -> u64 {
     }
 }
 
-#[cfg(target_os = "windows")]
 fn list_native_audio_input_devices_inner() -> Result<Vec<NativeAudioInputDevice>, String> {
-    initialize_mta().ok().map_err(|err| err.to_string())?;
-    let enumerator = DeviceEnumerator::new().map_err(|err| err.to_string())?;
-    let devices = enumerator
-        .get_device_collection(&AudioDirection::Capture)
-        .map_err(|err| err.to_string())?;
-    let mut result = Vec::new();
-    for device in (&devices).into_iter().flatten() {
-        result.push(NativeAudioInputDevice {
-            id: device.get_id().map_err(|err| err.to_string())?,
-            label: device.get_friendlyname().map_err(|err| err.to_string())?,
-        });
-    }
-    Ok(result)
+    Ok(audio_backend::default_backend()
+        .list_input_devices()?
+        .into_iter()
+        .map(|device| NativeAudioInputDevice {
+            id: device.id,
+            label: device.label,
+        })
+        .collect())
 }
 
-#[cfg(not(target_os = "windows"))]
-fn list_native_audio_input_devices_inner() -> Result<Vec<NativeAudioInputDevice>, String> {
-    Ok(Vec::new())
+/// Polls the capture device list and emits `native-audio-devices-changed` whenever a device
+/// appears, disappears or the default input moves. Polling keeps this identical across backends.
+fn start_native_audio_device_watcher(app: tauri::AppHandle) {
+    let _ = std::thread::Builder::new()
+        .name("native-audio-device-watcher".to_string())
+        .spawn(move || {
+            let backend = audio_backend::default_backend();
+            let mut known_ids: Option<Vec<String>> = None;
+            let mut known_default = backend.default_input_device_id();
+            loop {
+                if let Ok(devices) = backend.list_input_devices() {
+                    let ids: Vec<String> = devices.iter().map(|device| device.id.clone()).collect();
+                    let default_device_id = backend.default_input_device_id();
+                    if let Some(previous) = known_ids.as_ref() {
+                        let added: Vec<String> = ids
+                            .iter()
+                            .filter(|id| !previous.contains(id))
+                            .cloned()
+                            .collect();
+                        let removed: Vec<String> = previous
+                            .iter()
+                            .filter(|id| !ids.contains(id))
+                            .cloned()
+                            .collect();
+                        let default_changed = default_device_id != known_default;
+                        if !added.is_empty() || !removed.is_empty() || default_changed {
+                            let _ = app.emit(
+                                "native-audio-devices-changed",
+                                NativeAudioDevicesChangedPayload {
+                                    devices: devices
+                                        .into_iter()
+                                        .map(|device| NativeAudioInputDevice {
+                                            id: device.id,
+                                            label: device.label,
+                                        })
+                                        .collect(),
+                                    default_device_id: default_device_id.clone(),
+                                    added,
+                                    removed,
+                                    default_changed,
+                                },
+                            );
+                        }
+                    }
+                    known_ids = Some(ids);
+                    known_default = default_device_id;
+                }
+                std::thread::sleep(Duration::from_secs(2));
+            }
+        });
 }
 
-#[cfg(target_os = "windows")]
-fn resolve_capture_device(
-    enumerator: &DeviceEnumerator,
+/// Opens the microphone as mono, or with every channel the device has when the channel mode
+/// asks for it, resampled to the 48 kHz the processing chain runs at.
+fn open_native_input(
+    backend: &dyn CaptureBackend,
     device_id: Option<&str>,
-) -> Result<wasapi::Device, String> {
-    if let Some(device_id) = device_id {
-        if !device_id.is_empty() {
-            if let Ok(device) = enumerator.get_device(device_id) {
-                return Ok(device);
-            }
+    channel_mode: ChannelMode,
+) -> Result<(Box<dyn CaptureStream>, StreamFormat), String> {
+    let channels = match channel_mode {
+        ChannelMode::Mono => Some(1),
+        ChannelMode::Downmix | ChannelMode::Multichannel => None,
+    };
+    audio_backend::open_input_resampled(backend, device_id, channels, SAMPLE_RATE)
+}
+
+/// Keeps trying the default input device until one opens or the session is stopped.
+fn reopen_default_input(
+    backend: &dyn CaptureBackend,
+    channel_mode: ChannelMode,
+    stop_rx: &watch::Receiver<bool>,
+) -> Option<(Box<dyn CaptureStream>, StreamFormat)> {
+    loop {
+        if *stop_rx.borrow() {
+            return None;
         }
+        if let Ok(opened) = open_native_input(backend, None, channel_mode) {
+            return Some(opened);
+        }
+        std::thread::sleep(Duration::from_millis(500));
     }
-    enumerator
-        .get_default_device(&AudioDirection::Capture)
-        .map_err(|err| err.to_string())
 }
 
-#[cfg(target_os = "windows")]
-fn start_native_microphone_capture(
+struct EchoReferenceCapture {
+    stop_tx: watch::Sender<bool>,
+    thread: ThreadJoinHandle<()>,
+    processor: Arc<AudioProcessor>,
+    playback: Arc<PlaybackMixer>,
+}
+
+impl EchoReferenceCapture {
+    fn stop(self) {
+        self.playback.clear_echo_reference(&self.processor);
+        let _ = self.stop_tx.send(true);
+        let _ = self.thread.join();
+    }
+}
+
+/// Feeds the default render endpoint into the echo canceller as its far-end reference. While
+/// native playback runs, the playback mixer feeds its own output instead, which matches what
+/// reaches the speakers exactly, and the loopback capture is only drained.
+fn start_echo_reference_capture(
+    backend: Arc<dyn CaptureBackend>,
+    processor: Arc<AudioProcessor>,
+    playback: Arc<PlaybackMixer>,
+) -> Result<EchoReferenceCapture, String> {
+    playback.set_echo_reference(Arc::clone(&processor));
+    let thread_processor = Arc::clone(&processor);
+    let thread_playback = Arc::clone(&playback);
+    let (stop_tx, stop_rx) = watch::channel(false);
+    let thread = std::thread::Builder::new()
+        .name("native-microphone-echo-reference".to_string())
+        .spawn(move || {
+            let Ok((mut stream, _)) = audio_backend::open_render_loopback_resampled(
+                backend.as_ref(),
+                LoopbackTarget::System,
+                Some(1),
+                SAMPLE_RATE,
+            ) else {
+                return;
+            };
+
+            let mut pending = Vec::new();
+            loop {
+                if *stop_rx.borrow() {
+                    break;
+                }
+                if stream
+                    .read(&mut pending, Duration::from_millis(1000))
+                    .is_err()
+                {
+                    break;
+                }
+
+                if thread_playback.is_running() {
+                    pending.clear();
+                    continue;
+                }
+                let mut offset = 0;
+                while pending.len() - offset >= SAMPLES_PER_FRAME {
+                    let render_frame = &pending[offset..offset + SAMPLES_PER_FRAME];
+                    let _ = thread_processor.analyze_render_frame([render_frame]);
+                    offset += SAMPLES_PER_FRAME;
+                }
+                pending.drain(..offset);
+            }
+        })
+        .map_err(|err| err.to_string())?;
+    Ok(EchoReferenceCapture {
+        stop_tx,
+        thread,
+        processor,
+        playback,
+    })
+}
+
+/// Forwards 10 ms frames from a capture thread's ring into a LiveKit source. Frames are
+/// interleaved with `num_channels`. A capture side that produces nothing for a few frames is
+/// counted as an underrun; the task ends when stopped or when the producer closes the ring.
+fn start_audio_ring_publisher(
     rtc_source: NativeAudioSource,
-    mode: String,
-    input_sensitivity: f32,
-    device_id: Option<String>,
-    muted: Arc<AtomicBool>,
+    num_channels: u32,
+    ring: Arc<AudioRing>,
     stop_rx: watch::Receiver<bool>,
-) -> Result<(JoinHandle<()>, ThreadJoinHandle<()>), String> {
-    const SAMPLE_RATE: u32 = 48_000;
-    const CHANNELS: u32 = 1;
-    const SAMPLES_PER_CHANNEL: usize = 480;
-
-    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Vec<i16>>();
-    let mut task_stop_rx = stop_rx.clone();
-    let audio_task = tokio::spawn(async move {
+) -> JoinHandle<()> {
+    const STALL_TIMEOUT: Duration = Duration::from_millis(50);
+
+    let mut task_stop_rx = stop_rx;
+    tokio::spawn(async move {
+        let mut frame = vec![0.0f32; ring.frame_len()];
+        let samples_per_channel = (ring.frame_len() / num_channels.max(1) as usize) as u32;
         loop {
             tokio::select! {
                 changed = task_stop_rx.changed() => {
@@ -976,331 +1411,567 @@ fn start_native_microphone_capture(
                         break;
                     }
                 }
-                maybe_chunk = rx.recv() => {
-                    let Some(chunk) = maybe_chunk else { break; };
-                    let frame = AudioFrame {
-                        data: chunk.into(),
-                        sample_rate: SAMPLE_RATE,
-                        num_channels: CHANNELS,
-                        samples_per_channel: SAMPLES_PER_CHANNEL as u32,
-                    };
-                    let _ = rtc_source.capture_frame(&frame).await;
+                readable = tokio::time::timeout(STALL_TIMEOUT, ring.readable()) => {
+                    if readable.is_err() {
+                        ring.record_underrun();
+                    }
+                    while ring.pop_frame(&mut frame) {
+                        let data = frame
+                            .iter()
+                            .map(|sample| (sample.clamp(-1.0, 1.0) * 32767.0) as i16)
+                            .collect::<Vec<i16>>();
+                        let audio_frame = AudioFrame {
+                            data: data.into(),
+                            sample_rate: SAMPLE_RATE,
+                            num_channels,
+                            samples_per_channel,
+                        };
+                        let _ = rtc_source.capture_frame(&audio_frame).await;
+                    }
+                    if ring.is_closed() {
+                        break;
+                    }
                 }
             }
         }
-    });
+        ring.close();
+    })
+}
 
-    let audio_thread = std::thread::Builder::new()
-        .name("native-microphone-capture".to_string())
-        .spawn(move || {
-            if initialize_mta().is_err() {
-                return;
+/// Feeds every remote audio track the native room subscribes to into the recorder and the
+/// native playback mixer, one task per track, until the microphone session stops.
+fn start_remote_audio_feeds(
+    app: tauri::AppHandle,
+    events: tokio::sync::mpsc::UnboundedReceiver<RoomEvent>,
+    stop_rx: watch::Receiver<bool>,
+) -> JoinHandle<()> {
+    let mut events = events;
+    let mut task_stop_rx = stop_rx;
+    tokio::spawn(async move {
+        let mixer = Arc::clone(&app.state::<NativePlaybackManager>().mixer);
+        let ducking = Arc::clone(&app.state::<NativeDuckingManager>().ducking);
+        let mut feeds = HashMap::new();
+        loop {
+            tokio::select! {
+                changed = task_stop_rx.changed() => {
+                    if changed.is_err() || *task_stop_rx.borrow() {
+                        break;
+                    }
+                }
+                event = events.recv() => match event {
+                    Some(RoomEvent::TrackSubscribed {
+                        track: RemoteTrack::Audio(track),
+                        publication,
+                        participant,
+                        ..
+                    }) => {
+                        let identity = participant.identity().as_str().to_owned();
+                        let source = match publication.source() {
+                            TrackSource::ScreenshareAudio => PlaybackSource::ScreenShareAudio,
+                            _ => PlaybackSource::Microphone,
+                        };
+                        let tap = app.state::<NativeRecordingManager>().recorder.tap(
+                            identity.clone(),
+                            participant.name(),
+                            false,
+                            source,
+                        );
+                        let ring = mixer.attach(track.sid().to_string(), identity, source);
+                        // Only voices duck other audio, not someone else's shared sound.
+                        let ducking = (source == PlaybackSource::Microphone)
+                            .then(|| Arc::clone(&ducking));
+                        if let Some(previous) = feeds.insert(
+                            track.sid(),
+                            tokio::spawn(feed_remote_audio(track, tap, ring, ducking)),
+                        ) {
+                            previous.abort();
+                        }
+                    }
+                    Some(RoomEvent::TrackUnsubscribed {
+                        track: RemoteTrack::Audio(track),
+                        ..
+                    }) => {
+                        mixer.detach(&track.sid().to_string());
+                        if let Some(feed) = feeds.remove(&track.sid()) {
+                            feed.abort();
+                        }
+                    }
+                    Some(_) => {}
+                    None => break,
+                },
             }
+        }
+        for feed in feeds.into_values() {
+            feed.abort();
+        }
+        mixer.detach_all();
+    })
+}
 
-            let enumerator = match DeviceEnumerator::new() {
-                Ok(value) => value,
-                Err(_) => return,
-            };
-            let device = match resolve_capture_device(&enumerator, device_id.as_deref()) {
-                Ok(value) => value,
-                Err(_) => return,
-            };
-            let mut audio_client = match device.get_iaudioclient() {
-                Ok(value) => value,
-                Err(_) => return,
-            };
-            let desired_format = WaveFormat::new(
-                32,
-                32,
-                &SampleType::Float,
-                SAMPLE_RATE as usize,
-                CHANNELS as usize,
-                None,
-            );
-            let (_, min_time) = match audio_client.get_device_period() {
-                Ok(value) => value,
-                Err(_) => return,
-            };
-            let mode_config = StreamMode::EventsShared {
-                autoconvert: true,
-                buffer_duration_hns: min_time,
-            };
-            if audio_client
-                .initialize_client(&desired_format, &AudioDirection::Capture, &mode_config)
-                .is_err()
-            {
-                return;
-            }
+async fn feed_remote_audio(
+    track: RemoteAudioTrack,
+    tap: RecordingTap,
+    ring: Arc<AudioRing>,
+    ducking: Option<Arc<Ducking>>,
+) {
+    let mut stream = NativeAudioStream::new(track.rtc_track(), SAMPLE_RATE as i32, 1);
+    let mut samples = Vec::new();
+    let mut pending = Vec::new();
+    while let Some(frame) = stream.next().await {
+        samples.clear();
+        samples.extend(frame.data.iter().map(|sample| *sample as f32 / 32768.0));
+        tap.push(&samples, frame.num_channels as usize);
+        if let Some(ducking) = &ducking {
+            ducking.report_remote_frame(&samples);
+        }
 
-            let h_event = match audio_client.set_get_eventhandle() {
-                Ok(value) => value,
-                Err(_) => return,
+        pending.extend_from_slice(&samples);
+        let mut offset = 0;
+        while pending.len() - offset >= SAMPLES_PER_FRAME {
+            ring.push_frame(&pending[offset..offset + SAMPLES_PER_FRAME]);
+            offset += SAMPLES_PER_FRAME;
+        }
+        pending.drain(..offset);
+    }
+}
+
+/// Runs capture, gate and denoise on a dedicated thread and pushes each processed 10 ms frame,
+/// interleaved with the pipeline's output channel count, into `ring`. The thread exits when
+/// stopped or once the consumer closes the ring.
+fn start_native_microphone_capture(
+    pipeline: NativeMicrophonePipeline,
+    ring: Arc<AudioRing>,
+    options_rx: watch::Receiver<NativeMicrophoneOptions>,
+    stop_rx: watch::Receiver<bool>,
+) -> Result<ThreadJoinHandle<()>, String> {
+    const LEVEL_EVENT_FRAMES: usize = 5;
+    // Key clicks last a few tens of milliseconds after the press. Two presses this close
+    // together count as typing, which keeps the gate ducked until the burst ends.
+    const KEY_PRESS_HINT: Duration = Duration::from_millis(50);
+    const TYPING_BURST_GAP: Duration = Duration::from_millis(400);
+    const TYPING_DUCK: Duration = Duration::from_millis(300);
+
+    let audio_thread = std::thread::Builder::new()
+        .name("native-microphone-capture".to_string())
+        .spawn(move || {
+            let NativeMicrophonePipeline {
+                app,
+                backend,
+                mut voice,
+                muted,
+                output_channels,
+                native_channels,
+                recording,
+                mut soundboard,
+                ducking,
+            } = pipeline;
+            let with_channels = |mut options: NativeMicrophoneOptions| {
+                if native_channels {
+                    options.processing.channel_mode = ChannelMode::Multichannel;
+                }
+                options
             };
-            let capture_client = match audio_client.get_audiocaptureclient() {
+            let mut options_rx = options_rx;
+            let mut options = with_channels(options_rx.borrow_and_update().clone());
+            let mut local_stop_rx = stop_rx;
+            let emit_degraded =
+                |reason: String, requested_device_id: Option<String>, recovered: bool| {
+                    let _ = app.emit(
+                        "native-microphone-degraded",
+                        NativeMicrophoneDegradedPayload {
+                            reason,
+                            requested_device_id,
+                            fallback_device_id: recovered
+                                .then(|| backend.default_input_device_id())
+                                .flatten(),
+                            recovered,
+                        },
+                    );
+                };
+            let (mut stream, mut format) = match open_native_input(
+                backend.as_ref(),
+                options.device_id.as_deref(),
+                options.processing.channel_mode,
+            ) {
                 Ok(value) => value,
-                Err(_) => return,
+                Err(err) => {
+                    emit_degraded(err, options.device_id.clone(), false);
+                    match reopen_default_input(
+                        backend.as_ref(),
+                        options.processing.channel_mode,
+                        &local_stop_rx,
+                    ) {
+                        Some(value) => {
+                            emit_degraded(
+                                "Switched to the default input device".to_string(),
+                                options.device_id.take(),
+                                true,
+                            );
+                            value
+                        }
+                        None => return,
+                    }
+                }
             };
-            if audio_client.start_stream().is_err() {
-                return;
-            }
-
-            let mut local_stop_rx = stop_rx;
-            let mut byte_queue: VecDeque<u8> = VecDeque::new();
-            let chunk_bytes = SAMPLES_PER_CHANNEL * 4;
-            let mut denoiser = DenoiseState::new();
-            let mut rnnoise_output = [0.0f32; DenoiseState::FRAME_SIZE];
-            let close_threshold = input_sensitivity.clamp(0.004, 0.12);
-            let open_threshold = close_threshold * 1.2;
-            let close_frames = 18usize;
-            let use_rnnoise = mode == "rnnoise";
-            let mut gate_open = true;
-            let mut below_frames = 0usize;
+            let mut echo_reference = None;
+            // Key presses from the global input hook, only watched while typing suppression is on.
+            let mut keyboard: Option<KeyboardMonitor> = None;
+
+            let mut pending: Vec<f32> = Vec::new();
+            let mut processed: Vec<f32> = Vec::new();
+            let mut reconfigure = true;
+            let mut level_frames = 0usize;
+            let mut level_sum_squares = 0.0f32;
+            let mut level_peak = 0.0f32;
+            let mut was_speaking = false;
 
             loop {
                 if *local_stop_rx.borrow() {
-                    let _ = audio_client.stop_stream();
                     break;
                 }
 
-                let new_frames = match capture_client.get_next_packet_size() {
-                    Ok(Some(value)) => value,
-                    Ok(None) => 0,
-                    Err(_) => {
-                        let _ = audio_client.stop_stream();
-                        break;
-                    }
-                };
-                if new_frames > 0 {
-                    let additional = (new_frames as usize * 4)
-                        .saturating_sub(byte_queue.capacity().saturating_sub(byte_queue.len()));
-                    byte_queue.reserve(additional);
-                    if capture_client
-                        .read_from_device_to_deque(&mut byte_queue)
-                        .is_err()
+                if options_rx.has_changed().unwrap_or(false) {
+                    let mut next = with_channels(options_rx.borrow_and_update().clone());
+                    let channel_mode = next.processing.channel_mode;
+                    if next.device_id != options.device_id
+                        || channel_mode != options.processing.channel_mode
                     {
-                        let _ = audio_client.stop_stream();
-                        break;
+                        drop(stream);
+                        pending.clear();
+                        (stream, format) = match open_native_input(
+                            backend.as_ref(),
+                            next.device_id.as_deref(),
+                            channel_mode,
+                        ) {
+                            Ok(value) => value,
+                            Err(err) => {
+                                emit_degraded(err, next.device_id.clone(), false);
+                                match reopen_default_input(
+                                    backend.as_ref(),
+                                    channel_mode,
+                                    &local_stop_rx,
+                                ) {
+                                    Some(value) => {
+                                        emit_degraded(
+                                            "Switched to the default input device".to_string(),
+                                            next.device_id.take(),
+                                            true,
+                                        );
+                                        value
+                                    }
+                                    None => break,
+                                }
+                            }
+                        };
                     }
+                    options = next;
+                    reconfigure = true;
                 }
 
-                while byte_queue.len() >= chunk_bytes {
-                    let mut processed = vec![0.0f32; SAMPLES_PER_CHANNEL];
-                    for sample in &mut processed {
-                        let b0 = byte_queue.pop_front().unwrap_or_default();
-                        let b1 = byte_queue.pop_front().unwrap_or_default();
-                        let b2 = byte_queue.pop_front().unwrap_or_default();
-                        let b3 = byte_queue.pop_front().unwrap_or_default();
-                        *sample = f32::from_le_bytes([b0, b1, b2, b3]).clamp(-1.0, 1.0);
-                    }
-                    let rms = processed.iter().map(|sample| sample * sample).sum::<f32>()
-                        / (SAMPLES_PER_CHANNEL as f32);
-                    let rms = rms.sqrt();
-
-                    if rms >= open_threshold {
-                        gate_open = true;
-                        below_frames = 0;
-                    } else if rms <= close_threshold {
-                        below_frames = below_frames.saturating_add(1);
-                        if below_frames >= close_frames {
-                            gate_open = false;
+                if reconfigure {
+                    reconfigure = false;
+                    voice.configure(&options.processing);
+
+                    let echo_cancellation = options.processing.echo_cancellation;
+                    if echo_cancellation && echo_reference.is_none() {
+                        echo_reference = start_echo_reference_capture(
+                            Arc::clone(&backend),
+                            Arc::clone(voice.audio_processor()),
+                            Arc::clone(&app.state::<NativePlaybackManager>().mixer),
+                        )
+                        .ok();
+                    } else if !echo_cancellation {
+                        if let Some(reference) = echo_reference.take() {
+                            reference.stop();
                         }
-                    } else {
-                        below_frames = 0;
                     }
 
-                    let mut pcm = vec![0i16; SAMPLES_PER_CHANNEL];
-                    if !muted.load(Ordering::Relaxed) && gate_open {
-                        if use_rnnoise {
-                            let mut rnnoise_input = [0.0f32; DenoiseState::FRAME_SIZE];
-                            for (index, sample) in
-                                processed.iter().enumerate().take(SAMPLES_PER_CHANNEL)
-                            {
-                                rnnoise_input[index] = sample.clamp(-1.0, 1.0) * 32768.0;
-                            }
-                            denoiser.process_frame(&mut rnnoise_output, &rnnoise_input);
-                            for (index, out_sample) in rnnoise_output.iter().enumerate() {
-                                pcm[index] = out_sample.clamp(-32768.0, 32767.0) as i16;
-                            }
-                        } else {
-                            for (index, sample) in
-                                processed.iter().enumerate().take(SAMPLES_PER_CHANNEL)
-                            {
-                                pcm[index] = (sample.clamp(-1.0, 1.0) * 32767.0) as i16;
-                            }
-                        }
+                    let suppress_typing = options.processing.suppress_typing;
+                    if suppress_typing && keyboard.is_none() {
+                        keyboard = KeyboardMonitor::start(&app).ok();
+                    } else if !suppress_typing && keyboard.take().is_some() {
+                        voice.set_keyboard_activity(false, false);
                     }
+                }
 
-                    if tx.send(pcm).is_err() {
-                        let _ = audio_client.stop_stream();
-                        return;
+                if let Err(err) = stream.read(&mut pending, Duration::from_millis(1000)) {
+                    if *local_stop_rx.borrow() {
+                        break;
                     }
+                    drop(stream);
+                    pending.clear();
+                    emit_degraded(err, options.device_id.clone(), false);
+                    (stream, format) = match reopen_default_input(
+                        backend.as_ref(),
+                        options.processing.channel_mode,
+                        &local_stop_rx,
+                    ) {
+                        Some(value) => value,
+                        None => break,
+                    };
+                    emit_degraded(
+                        "Switched to the default input device".to_string(),
+                        options.device_id.take(),
+                        true,
+                    );
+                    continue;
                 }
 
-                if h_event.wait_for_event(1000).is_err() {
-                    let _ = audio_client.stop_stream();
+                let in_channels = format.channels as usize;
+                let frame_len = SAMPLES_PER_FRAME * in_channels;
+                let mut offset = 0;
+                while pending.len() - offset >= frame_len {
+                    if let Some(KeyboardMonitor { activity, .. }) = &keyboard {
+                        voice.set_keyboard_activity(
+                            activity.pressed_within(KEY_PRESS_HINT),
+                            activity.typing_within(TYPING_DUCK, TYPING_BURST_GAP),
+                        );
+                    }
+                    let decision = voice.process_interleaved(
+                        &pending[offset..offset + frame_len],
+                        in_channels,
+                        output_channels,
+                        &mut processed,
+                    );
+                    offset += frame_len;
+
+                    let is_muted = muted.load(Ordering::Relaxed);
+                    let speaking = !is_muted && decision.gate_open;
+                    if let Some(ducking) = ducking.as_ref().filter(|_| speaking) {
+                        ducking.report_voice();
+                    }
+                    level_frames += 1;
+                    level_sum_squares += decision.rms * decision.rms * SAMPLES_PER_FRAME as f32;
+                    level_peak = level_peak.max(decision.peak);
+                    if level_frames >= LEVEL_EVENT_FRAMES || speaking != was_speaking {
+                        let _ = app.emit(
+                            "native-microphone-level",
+                            NativeMicrophoneLevelPayload {
+                                rms: (level_sum_squares
+                                    / (level_frames * SAMPLES_PER_FRAME) as f32)
+                                    .sqrt(),
+                                peak: level_peak,
+                                voice_probability: decision.voice_probability,
+                                gate_open: decision.gate_open,
+                                speaking,
+                                agc_gain_estimate_db: decision.agc_gain_estimate_db,
+                            },
+                        );
+                        level_frames = 0;
+                        level_sum_squares = 0.0;
+                        level_peak = 0.0;
+                        was_speaking = speaking;
+                    }
+
+                    if is_muted {
+                        processed.fill(0.0);
+                    }
+                    // Clips go in after the gate so it never cuts them off.
+                    if let Some(soundboard) = soundboard.as_mut() {
+                        soundboard.mix_into(&mut processed, output_channels, |finished| {
+                            let _ = app.emit("native-soundboard-clip-finished", finished);
+                        });
+                    }
+                    if let Some(recording) = &recording {
+                        recording.push(&processed, output_channels);
+                    }
+                    ring.push_frame(&processed);
+                }
+                pending.drain(..offset);
+                if ring.is_closed() {
                     break;
                 }
+
                 if local_stop_rx.has_changed().unwrap_or(false) {
                     let _ = local_stop_rx.borrow_and_update();
                 }
             }
+
+            if let Some(reference) = echo_reference.take() {
+                reference.stop();
+            }
+            ring.close();
         })
         .map_err(|err| err.to_string())?;
 
-    Ok((audio_task, audio_thread))
+    Ok(audio_thread)
 }
 
-#[cfg(not(target_os = "windows"))]
-fn start_native_microphone_capture(
-    _rtc_source: NativeAudioSource,
-    _mode: String,
-    _input_sensitivity: f32,
-    _device_id: Option<String>,
-    _muted: Arc<AtomicBool>,
-    _stop_rx: watch::Receiver<bool>,
-) -> Result<(JoinHandle<()>, ThreadJoinHandle<()>), String> {
-    Err("Native microphone processing is currently available on Windows only".to_string())
+/// Plays processed microphone frames back to an output device, either as they arrive or, when
+/// `record_frames` is set, after recording that many frames.
+fn start_native_microphone_test_playback(
+    app: tauri::AppHandle,
+    backend: Arc<dyn PlaybackBackend>,
+    output_device_id: Option<String>,
+    record_frames: Option<usize>,
+    ring: Arc<AudioRing>,
+    stop_rx: watch::Receiver<bool>,
+) -> Result<ThreadJoinHandle<()>, String> {
+    std::thread::Builder::new()
+        .name("native-microphone-test-playback".to_string())
+        .spawn(move || {
+            let emit_phase = |phase: &'static str, error: Option<String>| {
+                let _ = app.emit(
+                    "native-microphone-test-state",
+                    NativeMicrophoneTestStatePayload { phase, error },
+                );
+            };
+            let mut output = match audio_backend::open_output_resampled(
+                backend.as_ref(),
+                output_device_id.as_deref(),
+                StreamFormat::new(SAMPLE_RATE, 1),
+            ) {
+                Ok(value) => value,
+                Err(err) => {
+                    emit_phase("failed", Some(err));
+                    return;
+                }
+            };
+            let mut frame = vec![0.0f32; ring.frame_len()];
+            let next_frame = |frame: &mut [f32]| loop {
+                if *stop_rx.borrow() || ring.is_closed() {
+                    return false;
+                }
+                if ring.pop_frame_blocking(frame, Duration::from_millis(100)) {
+                    return true;
+                }
+            };
+
+            let Some(record_frames) = record_frames else {
+                emit_phase("monitoring", None);
+                while next_frame(&mut frame) {
+                    if let Err(err) = output.write(&frame) {
+                        ring.close();
+                        emit_phase("failed", Some(err));
+                        return;
+                    }
+                }
+                ring.close();
+                emit_phase("finished", None);
+                return;
+            };
+
+            emit_phase("recording", None);
+            let mut recording = Vec::with_capacity(record_frames * SAMPLES_PER_FRAME);
+            while recording.len() < record_frames * SAMPLES_PER_FRAME && next_frame(&mut frame) {
+                recording.extend_from_slice(&frame);
+            }
+            // Closing the ring ends the capture thread; playback needs no microphone.
+            ring.close();
+
+            emit_phase("playing", None);
+            for chunk in recording.chunks(SAMPLES_PER_FRAME) {
+                if *stop_rx.borrow() {
+                    break;
+                }
+                if let Err(err) = output.write(chunk) {
+                    emit_phase("failed", Some(err));
+                    return;
+                }
+            }
+            emit_phase("finished", None);
+        })
+        .map_err(|err| err.to_string())
 }
 
-#[cfg(target_os = "windows")]
+/// Captures loopback audio for a screen share from the first of `targets` that opens, in
+/// order of preference, and returns the one in use. `None` means none of them opened.
+#[cfg(any(target_os = "windows", target_os = "linux"))]
 fn start_loopback_audio_capture(
+    backend: Arc<dyn CaptureBackend>,
+    targets: Vec<LoopbackTarget>,
+    ducking: Arc<Ducking>,
     rtc_source: NativeAudioSource,
     stop_rx: watch::Receiver<bool>,
-) -> Result<(JoinHandle<()>, ThreadJoinHandle<()>), String> {
-    const SAMPLE_RATE: u32 = 48_000;
+) -> Result<
+    (
+        JoinHandle<()>,
+        ThreadJoinHandle<()>,
+        Arc<AudioRing>,
+        Option<LoopbackTarget>,
+    ),
+    String,
+> {
     const CHANNELS: u32 = 2;
-    const SAMPLES_PER_CHANNEL: usize = 480;
 
-    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Vec<i16>>();
-    let mut task_stop_rx = stop_rx.clone();
-    let audio_task = tokio::spawn(async move {
-        loop {
-            tokio::select! {
-                changed = task_stop_rx.changed() => {
-                    if changed.is_err() || *task_stop_rx.borrow() {
-                        break;
-                    }
-                }
-                maybe_chunk = rx.recv() => {
-                    let Some(chunk) = maybe_chunk else { break; };
-                    let frame = AudioFrame {
-                        data: chunk.into(),
-                        sample_rate: SAMPLE_RATE,
-                        num_channels: CHANNELS,
-                        samples_per_channel: SAMPLES_PER_CHANNEL as u32,
-                    };
-                    let _ = rtc_source.capture_frame(&frame).await;
-                }
-            }
-        }
-    });
+    let chunk_samples = SAMPLES_PER_FRAME * CHANNELS as usize;
+    let ring = Arc::new(AudioRing::new(chunk_samples, NATIVE_AUDIO_RING_FRAMES));
+    let audio_task =
+        start_audio_ring_publisher(rtc_source, CHANNELS, Arc::clone(&ring), stop_rx.clone());
 
+    let (opened_tx, opened_rx) = mpsc::channel::<Option<LoopbackTarget>>();
     let thread_stop_rx = stop_rx;
+    let thread_ring = Arc::clone(&ring);
     let audio_thread = std::thread::Builder::new()
         .name("native-screen-share-audio".to_string())
         .spawn(move || {
-            if initialize_mta().is_err() {
-                return;
-            }
-
-            let enumerator = match DeviceEnumerator::new() {
-                Ok(value) => value,
-                Err(_) => return,
-            };
-            let device = match enumerator.get_default_device(&AudioDirection::Render) {
-                Ok(value) => value,
-                Err(_) => return,
-            };
-            let mut audio_client = match device.get_iaudioclient() {
-                Ok(value) => value,
-                Err(_) => return,
-            };
-            let desired_format = WaveFormat::new(
-                16,
-                16,
-                &SampleType::Int,
-                SAMPLE_RATE as usize,
-                CHANNELS as usize,
-                None,
-            );
-            let (_, min_time) = match audio_client.get_device_period() {
-                Ok(value) => value,
-                Err(_) => return,
-            };
-            let mode = StreamMode::EventsShared {
-                autoconvert: true,
-                buffer_duration_hns: min_time,
-            };
-            if audio_client
-                .initialize_client(&desired_format, &AudioDirection::Capture, &mode)
-                .is_err()
-            {
+            // Process loopback needs a recent OS and a process that is still running, so
+            // each target falls back to the next.
+            let opened = targets.into_iter().find_map(|target| {
+                audio_backend::open_render_loopback_resampled(
+                    backend.as_ref(),
+                    target,
+                    Some(CHANNELS as u16),
+                    SAMPLE_RATE,
+                )
+                .ok()
+                .map(|(stream, _)| (stream, target))
+            });
+            let _ = opened_tx.send(opened.as_ref().map(|(_, target)| *target));
+            let Some((mut stream, _)) = opened else {
+                thread_ring.close();
                 return;
-            }
-
-            let h_event = match audio_client.set_get_eventhandle() {
-                Ok(value) => value,
-                Err(_) => return,
-            };
-            let capture_client = match audio_client.get_audiocaptureclient() {
-                Ok(value) => value,
-                Err(_) => return,
             };
-            if audio_client.start_stream().is_err() {
-                return;
-            }
 
-            let mut byte_queue: VecDeque<u8> = VecDeque::new();
-            let chunk_bytes = SAMPLES_PER_CHANNEL * CHANNELS as usize * 2;
+            let mut ducker = Ducker::new(ducking, false);
+            let mut pending: Vec<f32> = Vec::new();
             loop {
-                if *thread_stop_rx.borrow() {
-                    let _ = audio_client.stop_stream();
+                if *thread_stop_rx.borrow() || thread_ring.is_closed() {
                     break;
                 }
 
-                let new_frames = match capture_client.get_next_packet_size() {
-                    Ok(Some(value)) => value,
-                    Ok(None) => 0,
-                    Err(_) => {
-                        let _ = audio_client.stop_stream();
-                        break;
-                    }
-                };
-                if new_frames > 0 {
-                    let additional = (new_frames as usize * 2 * CHANNELS as usize)
-                        .saturating_sub(byte_queue.capacity().saturating_sub(byte_queue.len()));
-                    byte_queue.reserve(additional);
-                    if capture_client
-                        .read_from_device_to_deque(&mut byte_queue)
-                        .is_err()
-                    {
-                        let _ = audio_client.stop_stream();
-                        break;
-                    }
-                }
-
-                while byte_queue.len() >= chunk_bytes {
-                    let mut pcm = Vec::with_capacity(SAMPLES_PER_CHANNEL * CHANNELS as usize);
-                    for _ in 0..(SAMPLES_PER_CHANNEL * CHANNELS as usize) {
-                        let lo = byte_queue.pop_front().unwrap_or_default();
-                        let hi = byte_queue.pop_front().unwrap_or_default();
-                        pcm.push(i16::from_le_bytes([lo, hi]));
-                    }
-                    if tx.send(pcm).is_err() {
-                        let _ = audio_client.stop_stream();
-                        return;
-                    }
+                if stream
+                    .read(&mut pending, Duration::from_millis(1000))
+                    .is_err()
+                {
+                    break;
                 }
 
-                if h_event.wait_for_event(1000).is_err() {
-                    let _ = audio_client.stop_stream();
-                    break;
+                let mut offset = 0;
+                while pending.len() - offset >= chunk_samples {
+                    let chunk = &mut pending[offset..offset + chunk_samples];
+                    ducker.process(chunk, CHANNELS as usize);
+                    thread_ring.push_frame(chunk);
+                    offset += chunk_samples;
                 }
+                pending.drain(..offset);
             }
+            thread_ring.close();
         })
         .map_err(|err| err.to_string())?;
 
-    Ok((audio_task, audio_thread))
+    let target = opened_rx
+        .recv_timeout(Duration::from_secs(3))
+        .ok()
+        .flatten();
+    Ok((audio_task, audio_thread, ring, target))
+}
+
+/// The process that owns a window capture source, whose audio a window share carries.
+#[cfg(target_os = "windows")]
+fn window_process_id(window_id: u64) -> Option<u32> {
+    use windows_sys::Win32::UI::WindowsAndMessaging::GetWindowThreadProcessId;
+
+    let mut process_id = 0u32;
+    // WebRTC identifies window sources by their HWND.
+    let thread_id = unsafe { GetWindowThreadProcessId(window_id as usize as _, &mut process_id) };
+    (thread_id != 0 && process_id != 0).then_some(process_id)
+}
+
+/// The Linux loopback backend records the whole monitor source, so there is nothing to look up.
+#[cfg(target_os = "linux")]
+fn window_process_id(_window_id: u64) -> Option<u32> {
+    None
 }
 
 fn capture_preview_for_source(
@@ -1402,6 +2073,13 @@ async fn stop_native_screen_share_inner(manager: &NativeScreenShareManager) -> R
     Ok(())
 }
 
+/// Whether screen-share audio can leave out this app's own playback. Only WASAPI process
+/// loopback can; a PulseAudio or PipeWire monitor carries every stream the sink plays.
+#[tauri::command]
+fn native_screen_share_excludes_own_audio() -> bool {
+    cfg!(target_os = "windows")
+}
+
 #[tauri::command]
 async fn list_native_screen_share_sources() -> Result<Vec<NativeScreenShareSource>, String> {
     tokio::task::spawn_blocking(|| {
@@ -1417,7 +2095,8 @@ async fn list_native_screen_share_sources() -> Result<Vec<NativeScreenShareSourc
 async fn start_native_screen_share(
     options: NativeScreenShareStartOptions,
     manager: State<'_, NativeScreenShareManager>,
-) -> Result<(), String> {
+    ducking: State<'_, NativeDuckingManager>,
+) -> Result<NativeScreenShareStarted, String> {
     stop_native_screen_share_inner(&manager).await?;
 
     let selected = options
@@ -1534,8 +2213,8 @@ async fn start_native_screen_share(
         .map_err(|err| err.to_string())?;
 
     let (stop_tx, mut stop_rx) = watch::channel(false);
-    #[cfg(target_os = "windows")]
-    let (audio_task, audio_thread) = {
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    let (audio_task, audio_thread, audio_ring, audio_mode) = {
         let audio_rtc_source =
             NativeAudioSource::new(AudioSourceOptions::default(), 48_000, 2, 500);
         let audio_track = LocalAudioTrack::create_audio_track(
@@ -1554,12 +2233,45 @@ async fn start_native_screen_share(
             .await
             .map_err(|err| err.to_string())?;
 
-        let (task, thread) = start_loopback_audio_capture(audio_rtc_source, stop_tx.subscribe())?;
-        (Some(task), Some(thread))
+        let own_process = std::process::id();
+        let mut targets = Vec::new();
+        if selected.kind == "window" {
+            if let Some(process_id) =
+                window_process_id(selected.id).filter(|process_id| *process_id != own_process)
+            {
+                targets.push(LoopbackTarget::IncludeProcessTree(process_id));
+            }
+        }
+        // The webview renders the voice channel from child processes of this one, so excluding
+        // the process tree covers it.
+        if options.exclude_own_audio && native_screen_share_excludes_own_audio() {
+            targets.push(LoopbackTarget::ExcludeProcessTree(own_process));
+        }
+        targets.push(LoopbackTarget::System);
+        let (task, thread, ring, target) = start_loopback_audio_capture(
+            audio_backend::default_backend(),
+            targets,
+            Arc::clone(&ducking.ducking),
+            audio_rtc_source,
+            stop_tx.subscribe(),
+        )?;
+        (
+            Some(task),
+            Some(thread),
+            Some(ring),
+            target.map(NativeScreenShareAudioMode::from),
+        )
     };
-    #[cfg(not(target_os = "windows"))]
-    let (audio_task, audio_thread): (Option<JoinHandle<()>>, Option<ThreadJoinHandle<()>>) =
-        (None, None);
+    // Only screen share audio is ducked, which this platform does not capture.
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    let _ = &ducking;
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    let (audio_task, audio_thread, audio_ring, audio_mode): (
+        Option<JoinHandle<()>>,
+        Option<ThreadJoinHandle<()>>,
+        Option<Arc<AudioRing>>,
+        Option<NativeScreenShareAudioMode>,
+    ) = (None, None, None, None);
 
     let initial_frame = first_frame.to_video_frame(out_width, out_height);
     rtc_source.capture_frame(&initial_frame);
@@ -1586,6 +2298,7 @@ async fn start_native_screen_share(
         capture_task,
         audio_task,
         audio_thread,
+        audio_ring,
         stop_tx,
     };
     let mut guard = manager
@@ -1593,7 +2306,7 @@ async fn start_native_screen_share(
         .lock()
         .map_err(|_| "Native screen share lock poisoned".to_string())?;
     *guard = Some(session);
-    Ok(())
+    Ok(NativeScreenShareStarted { audio_mode })
 }
 
 #[tauri::command]
@@ -1611,90 +2324,266 @@ async fn stop_native_microphone_inner(manager: &NativeMicrophoneManager) -> Resu
             .map_err(|_| "Native microphone lock poisoned")?;
         guard.take()
     };
+    match existing {
+        Some(session) => close_native_microphone_session(session).await,
+        None => Ok(()),
+    }
+}
+
+async fn close_native_microphone_session(session: NativeMicrophoneSession) -> Result<(), String> {
+    let _ = session.stop_tx.send(true);
+    let _ = session.audio_task.await;
+    let _ = session.remote_audio_task.await;
+    let _ = session.capture_thread.join();
+    session.room.close().await.map_err(|err| err.to_string())
+}
+
+/// Whether this build captures the microphone natively. Decided by the target the app was built
+/// for rather than anything the webview reports.
+#[tauri::command]
+fn native_microphone_supported() -> bool {
+    cfg!(any(target_os = "windows", target_os = "linux"))
+}
+
+#[tauri::command]
+fn list_native_audio_input_devices() -> Result<Vec<NativeAudioInputDevice>, String> {
+    list_native_audio_input_devices_inner()
+}
+
+#[tauri::command]
+fn list_native_audio_output_devices() -> Result<Vec<NativeAudioInputDevice>, String> {
+    Ok(audio_backend::default_playback_backend()
+        .list_output_devices()?
+        .into_iter()
+        .map(|device| NativeAudioInputDevice {
+            id: device.id,
+            label: device.label,
+        })
+        .collect())
+}
+
+fn stop_native_microphone_test_inner(manager: &NativeMicrophoneTestManager) -> Result<(), String> {
+    let existing = {
+        let mut guard = manager
+            .session
+            .lock()
+            .map_err(|_| "Native microphone test lock poisoned")?;
+        guard.take()
+    };
     if let Some(session) = existing {
         let _ = session.stop_tx.send(true);
-        let _ = session.audio_task.await;
         let _ = session.capture_thread.join();
-        session.room.close().await.map_err(|err| err.to_string())?;
+        let _ = session.playback_thread.join();
     }
     Ok(())
 }
 
 #[tauri::command]
-fn list_native_audio_input_devices() -> Result<Vec<NativeAudioInputDevice>, String> {
-    list_native_audio_input_devices_inner()
+async fn start_native_microphone_test(
+    app: tauri::AppHandle,
+    options: NativeMicrophoneTestOptions,
+    manager: State<'_, NativeMicrophoneTestManager>,
+) -> Result<(), String> {
+    stop_native_microphone_test_inner(&manager)?;
+
+    let (stop_tx, stop_rx) = watch::channel(false);
+    let (options_tx, options_rx) = watch::channel(options.processing);
+    let ring = Arc::new(AudioRing::new(SAMPLES_PER_FRAME, NATIVE_AUDIO_RING_FRAMES));
+    let record_frames = options
+        .record_seconds
+        .filter(|seconds| *seconds > 0.0)
+        .map(|seconds| (seconds.min(30.0) * 100.0).round() as usize);
+    let playback_thread = start_native_microphone_test_playback(
+        app.clone(),
+        audio_backend::default_playback_backend(),
+        options.output_device_id,
+        record_frames,
+        Arc::clone(&ring),
+        stop_rx.clone(),
+    )?;
+    let pipeline = NativeMicrophonePipeline {
+        recording: None,
+        soundboard: None,
+        ducking: None,
+        app,
+        backend: audio_backend::default_backend(),
+        voice: VoiceProcessor::new(&options.processing.processing)?,
+        muted: Arc::new(AtomicBool::new(false)),
+        output_channels: 1,
+        native_channels: false,
+    };
+    let capture_thread = start_native_microphone_capture(pipeline, ring, options_rx, stop_rx)?;
+
+    let mut guard = manager
+        .session
+        .lock()
+        .map_err(|_| "Native microphone test lock poisoned".to_string())?;
+    *guard = Some(NativeMicrophoneTestSession {
+        capture_thread,
+        playback_thread,
+        stop_tx,
+        options_tx,
+    });
+    Ok(())
 }
 
 #[tauri::command]
-async fn start_native_microphone(
-    options: NativeMicrophoneStartOptions,
-    manager: State<'_, NativeMicrophoneManager>,
+fn set_native_microphone_test_options(
+    options: NativeMicrophoneOptions,
+    manager: State<'_, NativeMicrophoneTestManager>,
 ) -> Result<(), String> {
-    #[cfg(not(target_os = "windows"))]
-    {
-        let _ = options;
-        let _ = manager;
-        return Err(
-            "Native microphone processing is currently available on Windows only".to_string(),
-        );
+    let guard = manager
+        .session
+        .lock()
+        .map_err(|_| "Native microphone test lock poisoned".to_string())?;
+    if let Some(session) = guard.as_ref() {
+        session.options_tx.send_replace(options);
     }
+    Ok(())
+}
 
-    #[cfg(target_os = "windows")]
-    {
-        stop_native_microphone_inner(&manager).await?;
+#[tauri::command]
+async fn stop_native_microphone_test(
+    manager: State<'_, NativeMicrophoneTestManager>,
+) -> Result<(), String> {
+    stop_native_microphone_test_inner(&manager)
+}
 
-        let (room, _events) =
-            Room::connect(&options.livekit_url, &options.token, RoomOptions::default())
-                .await
-                .map_err(|err| err.to_string())?;
+#[tauri::command]
+async fn start_native_microphone(
+    app: tauri::AppHandle,
+    options: NativeMicrophoneStartOptions,
+    manager: State<'_, NativeMicrophoneManager>,
+) -> Result<(), String> {
+    stop_native_microphone_inner(&manager).await?;
 
-        let rtc_source = NativeAudioSource::new(AudioSourceOptions::default(), 48_000, 1, 0);
-        let track = LocalAudioTrack::create_audio_track(
-            "native-microphone",
-            RtcAudioSource::Native(rtc_source.clone()),
-        );
-        let mut publish_options = TrackPublishOptions::default();
-        publish_options.source = TrackSource::Microphone;
-        publish_options.simulcast = false;
-        publish_options.dtx = false;
-        publish_options.audio_encoding = Some(AudioEncoding {
-            max_bitrate: 96_000,
-        });
-        room.local_participant()
-            .publish_track(LocalTrack::Audio(track.clone()), publish_options)
+    let (room, events) =
+        Room::connect(&options.livekit_url, &options.token, RoomOptions::default())
             .await
             .map_err(|err| err.to_string())?;
-
-        let (stop_tx, stop_rx) = watch::channel(false);
-        let muted = Arc::new(AtomicBool::new(options.start_muted));
-        if options.start_muted {
-            track.mute();
+    let (stop_tx, stop_rx) = watch::channel(false);
+    let remote_audio_task = start_remote_audio_feeds(app.clone(), events, stop_rx.clone());
+
+    let published = match publish_native_microphone(app, options, &room, stop_rx).await {
+        Ok(published) => published,
+        Err(err) => {
+            let _ = stop_tx.send(true);
+            let _ = remote_audio_task.await;
+            let _ = room.close().await;
+            return Err(err);
+        }
+    };
+    let session = NativeMicrophoneSession {
+        room,
+        track: published.track,
+        audio_task: published.audio_task,
+        capture_thread: published.capture_thread,
+        stop_tx,
+        options_tx: published.options_tx,
+        muted: published.muted,
+        processor: published.processor,
+        ring: published.ring,
+        remote_audio_task,
+    };
+    let rejected = match manager.session.lock() {
+        Ok(mut guard) => {
+            *guard = Some(session);
+            None
         }
+        Err(_) => Some(session),
+    };
+    if let Some(session) = rejected {
+        let _ = close_native_microphone_session(session).await;
+        return Err("Native microphone lock poisoned".to_string());
+    }
+    Ok(())
+}
 
-        let (audio_task, capture_thread) = start_native_microphone_capture(
-            rtc_source,
-            options.noise_suppression_mode.clone(),
-            options.input_sensitivity,
-            options.device_id.clone(),
-            Arc::clone(&muted),
-            stop_rx,
-        )?;
+/// The parts of a [`NativeMicrophoneSession`] that exist once the track is published.
+struct PublishedNativeMicrophone {
+    track: LocalAudioTrack,
+    audio_task: JoinHandle<()>,
+    capture_thread: ThreadJoinHandle<()>,
+    options_tx: watch::Sender<NativeMicrophoneOptions>,
+    muted: Arc<AtomicBool>,
+    processor: Arc<AudioProcessor>,
+    ring: Arc<AudioRing>,
+}
 
-        let session = NativeMicrophoneSession {
-            room,
-            track,
-            audio_task,
-            capture_thread,
-            stop_tx,
-            muted,
-        };
-        let mut guard = manager
-            .session
-            .lock()
-            .map_err(|_| "Native microphone lock poisoned".to_string())?;
-        *guard = Some(session);
-        Ok(())
+/// Publishes the microphone track on `room` and starts capturing into it. Whatever this started
+/// before failing winds down once the caller signals `stop_rx` and closes the room.
+async fn publish_native_microphone(
+    app: tauri::AppHandle,
+    options: NativeMicrophoneStartOptions,
+    room: &Room,
+    stop_rx: watch::Receiver<bool>,
+) -> Result<PublishedNativeMicrophone, String> {
+    let quality_profile = options.quality_profile;
+    let num_channels = if options.stereo {
+        2
+    } else {
+        quality_profile.channels()
+    };
+    let rtc_source = NativeAudioSource::new(AudioSourceOptions::default(), 48_000, num_channels, 0);
+    let track = LocalAudioTrack::create_audio_track(
+        "native-microphone",
+        RtcAudioSource::Native(rtc_source.clone()),
+    );
+    let mut publish_options = TrackPublishOptions::default();
+    publish_options.source = TrackSource::Microphone;
+    publish_options.simulcast = false;
+    quality_profile.apply(&mut publish_options);
+    room.local_participant()
+        .publish_track(LocalTrack::Audio(track.clone()), publish_options)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let muted = Arc::new(AtomicBool::new(options.start_muted));
+    if options.start_muted {
+        track.mute();
     }
+
+    let music = quality_profile == NativeMicrophoneQualityProfile::MusicStereo;
+    let mut voice = VoiceProcessor::new(&options.processing.processing)?;
+    voice.set_bypass(music);
+    let processor = Arc::clone(voice.audio_processor());
+    let (options_tx, options_rx) = watch::channel(options.processing);
+    let local_participant = room.local_participant();
+    let recording = app.state::<NativeRecordingManager>().recorder.tap(
+        local_participant.identity().as_str().to_owned(),
+        local_participant.name(),
+        true,
+        PlaybackSource::Microphone,
+    );
+    let pipeline = NativeMicrophonePipeline {
+        recording: Some(recording),
+        soundboard: Some(app.state::<NativeSoundboardManager>().soundboard.attach()),
+        ducking: Some(Arc::clone(&app.state::<NativeDuckingManager>().ducking)),
+        app,
+        backend: audio_backend::default_backend(),
+        voice,
+        muted: Arc::clone(&muted),
+        output_channels: num_channels as usize,
+        native_channels: music,
+    };
+    let ring = Arc::new(AudioRing::new(
+        SAMPLES_PER_FRAME * num_channels as usize,
+        NATIVE_AUDIO_RING_FRAMES,
+    ));
+    let audio_task =
+        start_audio_ring_publisher(rtc_source, num_channels, Arc::clone(&ring), stop_rx.clone());
+    let capture_thread =
+        start_native_microphone_capture(pipeline, Arc::clone(&ring), options_rx, stop_rx)?;
+
+    Ok(PublishedNativeMicrophone {
+        track,
+        audio_task,
+        capture_thread,
+        options_tx,
+        muted,
+        processor,
+        ring,
+    })
 }
 
 #[tauri::command]
@@ -1702,10 +2591,9 @@ async fn stop_native_microphone(manager: State<'_, NativeMicrophoneManager>) ->
     stop_native_microphone_inner(&manager).await
 }
 
-#[tauri::command]
-fn set_native_microphone_muted(
+fn set_native_microphone_muted_inner(
+    manager: &NativeMicrophoneManager,
     muted: bool,
-    manager: State<'_, NativeMicrophoneManager>,
 ) -> Result<(), String> {
     let guard = manager
         .session
@@ -1722,11 +2610,357 @@ fn set_native_microphone_muted(
     Ok(())
 }
 
+/// Sets the mute the UI asks for; push-to-talk, when bound, only opens the microphone on top of
+/// an unmuted UI.
+#[tauri::command]
+fn set_native_microphone_muted(
+    muted: bool,
+    manager: State<'_, NativeMicrophoneManager>,
+    push_to_talk: State<'_, NativePushToTalkManager>,
+) -> Result<(), String> {
+    push_to_talk.ui_muted.store(muted, Ordering::SeqCst);
+    set_native_microphone_muted_inner(&manager, push_to_talk.microphone_muted())
+}
+
+/// Drives the native microphone from the global input hook. Releases are held back by the
+/// configured delay so the tail of a word is not clipped; a new press cancels a pending release.
+fn handle_push_to_talk_input(app: &tauri::AppHandle, event: &rdev::EventType) {
+    let push_to_talk = app.state::<NativePushToTalkManager>();
+    let transition = push_to_talk.binding.lock().ok().and_then(|binding| {
+        binding
+            .as_ref()
+            .and_then(|binding| binding.transition(event))
+    });
+    let Some(pressed) = transition else {
+        return;
+    };
+
+    if pressed {
+        if push_to_talk.pressed.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        push_to_talk
+            .release_generation
+            .fetch_add(1, Ordering::SeqCst);
+        if push_to_talk.microphone_muted() {
+            return;
+        }
+        let _ = set_native_microphone_muted_inner(&app.state::<NativeMicrophoneManager>(), false);
+        let _ = app.emit(
+            "native-push-to-talk",
+            NativePushToTalkPayload { pressed: true },
+        );
+        return;
+    }
+
+    if !push_to_talk.pressed.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let generation = push_to_talk
+        .release_generation
+        .fetch_add(1, Ordering::SeqCst)
+        + 1;
+    let delay = Duration::from_millis(push_to_talk.release_delay_ms.load(Ordering::SeqCst));
+    let app = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(delay);
+        let push_to_talk = app.state::<NativePushToTalkManager>();
+        if push_to_talk.release_generation.load(Ordering::SeqCst) != generation
+            || push_to_talk.pressed.load(Ordering::SeqCst)
+        {
+            return;
+        }
+        let _ = set_native_microphone_muted_inner(
+            &app.state::<NativeMicrophoneManager>(),
+            push_to_talk.microphone_muted(),
+        );
+        let _ = app.emit(
+            "native-push-to-talk",
+            NativePushToTalkPayload { pressed: false },
+        );
+    });
+}
+
+#[tauri::command]
+fn set_native_push_to_talk(
+    app: tauri::AppHandle,
+    binding: Option<String>,
+    release_delay_ms: Option<u64>,
+    push_to_talk: State<'_, NativePushToTalkManager>,
+    microphone: State<'_, NativeMicrophoneManager>,
+) -> Result<(), String> {
+    let binding = match binding.as_deref().filter(|code| !code.is_empty()) {
+        Some(code) => Some(
+            InputBinding::parse(code)
+                .ok_or_else(|| format!("Unsupported push-to-talk binding: {code}"))?,
+        ),
+        None => None,
+    };
+    push_to_talk
+        .release_delay_ms
+        .store(release_delay_ms.unwrap_or(0).min(2_000), Ordering::SeqCst);
+    let enabled = binding.is_some();
+    *push_to_talk
+        .binding
+        .lock()
+        .map_err(|_| "Push-to-talk lock poisoned".to_string())? = binding;
+    if !enabled {
+        push_to_talk.pressed.store(false, Ordering::SeqCst);
+        push_to_talk
+            .release_generation
+            .fetch_add(1, Ordering::SeqCst);
+    }
+    // Back to the UI's mute state when disabled, closed until pressed when enabled.
+    set_native_microphone_muted_inner(&microphone, push_to_talk.microphone_muted())?;
+    if !enabled {
+        return Ok(());
+    }
+
+    start_global_input_hook(&app)
+}
+
+/// Installs the global input hook on first use by push-to-talk or a [`KeyboardMonitor`]. It
+/// stays installed for the rest of the session but ignores input while neither is active.
+fn start_global_input_hook(app: &tauri::AppHandle) -> Result<(), String> {
+    let input = app.state::<GlobalInputManager>();
+    if input.hook_started.swap(true, Ordering::SeqCst) {
+        return Ok(());
+    }
+    let hook_app = app.clone();
+    if let Err(err) = global_input::spawn_listener(move |event| {
+        let input = hook_app.state::<GlobalInputManager>();
+        if input.keyboard_monitors.load(Ordering::SeqCst) > 0 {
+            input.keyboard.record(event);
+        }
+        handle_push_to_talk_input(&hook_app, event);
+    }) {
+        input.hook_started.store(false, Ordering::SeqCst);
+        return Err(err);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn set_native_microphone_options(
+    options: NativeMicrophoneOptions,
+    manager: State<'_, NativeMicrophoneManager>,
+) -> Result<(), String> {
+    let guard = manager
+        .session
+        .lock()
+        .map_err(|_| "Native microphone lock poisoned".to_string())?;
+    if let Some(session) = guard.as_ref() {
+        session.options_tx.send_replace(options);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn get_native_microphone_echo_stats(
+    manager: State<'_, NativeMicrophoneManager>,
+) -> Result<Option<NativeMicrophoneEchoStats>, String> {
+    let guard = manager
+        .session
+        .lock()
+        .map_err(|_| "Native microphone lock poisoned".to_string())?;
+    Ok(guard
+        .as_ref()
+        .filter(|session| session.options_tx.borrow().processing.echo_cancellation)
+        .map(|session| session.processor.get_stats().into()))
+}
+
+#[tauri::command]
+fn start_native_recording(
+    options: RecordingOptions,
+    manager: State<'_, NativeRecordingManager>,
+) -> Result<(), String> {
+    manager.recorder.start(options)
+}
+
+#[tauri::command]
+async fn stop_native_recording(
+    manager: State<'_, NativeRecordingManager>,
+) -> Result<Option<RecordingSummary>, String> {
+    manager.recorder.stop()
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NativeSoundboardClipInfo {
+    clip_id: String,
+    duration_ms: u64,
+}
+
+#[tauri::command]
+async fn load_native_soundboard_clip(
+    clip_id: String,
+    path: String,
+    volume: Option<f32>,
+    manager: State<'_, NativeSoundboardManager>,
+) -> Result<NativeSoundboardClipInfo, String> {
+    let clip = tokio::task::spawn_blocking(move || SoundboardClip::load(Path::new(&path)))
+        .await
+        .map_err(|err| err.to_string())??;
+    let duration_ms = clip.duration_ms();
+    manager
+        .soundboard
+        .insert(clip_id.clone(), clip, volume.unwrap_or(1.0));
+    Ok(NativeSoundboardClipInfo {
+        clip_id,
+        duration_ms,
+    })
+}
+
+#[tauri::command]
+fn unload_native_soundboard_clip(
+    clip_id: String,
+    manager: State<'_, NativeSoundboardManager>,
+) -> Result<(), String> {
+    manager.soundboard.remove(&clip_id);
+    Ok(())
+}
+
+#[tauri::command]
+fn play_native_soundboard_clip(
+    clip_id: String,
+    manager: State<'_, NativeSoundboardManager>,
+) -> Result<u64, String> {
+    manager.soundboard.play(&clip_id)
+}
+
+#[tauri::command]
+fn set_native_soundboard_clip_volume(
+    clip_id: String,
+    volume: f32,
+    manager: State<'_, NativeSoundboardManager>,
+) -> Result<(), String> {
+    manager.soundboard.set_volume(&clip_id, volume)
+}
+
+#[tauri::command]
+fn stop_native_soundboard(manager: State<'_, NativeSoundboardManager>) -> Result<(), String> {
+    manager.soundboard.stop_all();
+    Ok(())
+}
+
+fn stop_native_playback_inner(manager: &NativePlaybackManager) -> Result<(), String> {
+    let existing = {
+        let mut guard = manager
+            .session
+            .lock()
+            .map_err(|_| "Native playback lock poisoned")?;
+        guard.take()
+    };
+    if let Some(session) = existing {
+        session.stop();
+    }
+    Ok(())
+}
+
+/// Plays the remote audio of the native room through `output_device_id` instead of the
+/// webview. Remote tracks are only received while the native microphone is running.
+#[tauri::command]
+async fn start_native_playback(
+    output_device_id: Option<String>,
+    manager: State<'_, NativePlaybackManager>,
+    ducking: State<'_, NativeDuckingManager>,
+) -> Result<(), String> {
+    stop_native_playback_inner(&manager)?;
+    let session = PlaybackSession::start(
+        audio_backend::default_playback_backend(),
+        Arc::clone(&manager.mixer),
+        Arc::clone(&ducking.ducking),
+        output_device_id,
+    )?;
+    let mut guard = manager
+        .session
+        .lock()
+        .map_err(|_| "Native playback lock poisoned".to_string())?;
+    *guard = Some(session);
+    Ok(())
+}
+
+#[tauri::command]
+async fn stop_native_playback(manager: State<'_, NativePlaybackManager>) -> Result<(), String> {
+    stop_native_playback_inner(&manager)
+}
+
+#[tauri::command]
+fn set_native_playback_output_device(
+    output_device_id: Option<String>,
+    manager: State<'_, NativePlaybackManager>,
+) -> Result<(), String> {
+    let guard = manager
+        .session
+        .lock()
+        .map_err(|_| "Native playback lock poisoned".to_string())?;
+    if let Some(session) = guard.as_ref() {
+        session.set_device(output_device_id);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn set_native_playback_mixes(
+    mixes: Vec<PlaybackMixEntry>,
+    manager: State<'_, NativePlaybackManager>,
+) -> Result<(), String> {
+    manager.mixer.set_mixes(mixes);
+    Ok(())
+}
+
+#[tauri::command]
+fn set_native_ducking(
+    settings: DuckingSettings,
+    manager: State<'_, NativeDuckingManager>,
+) -> Result<(), String> {
+    manager.ducking.configure(settings);
+    Ok(())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NativeAudioPipelineStats {
+    microphone: Option<AudioRingStats>,
+    screen_share_audio: Option<AudioRingStats>,
+}
+
+#[tauri::command]
+fn get_native_audio_pipeline_stats(
+    microphone: State<'_, NativeMicrophoneManager>,
+    screen_share: State<'_, NativeScreenShareManager>,
+) -> Result<NativeAudioPipelineStats, String> {
+    let microphone = microphone
+        .session
+        .lock()
+        .map_err(|_| "Native microphone lock poisoned".to_string())?
+        .as_ref()
+        .map(|session| session.ring.stats());
+    let screen_share_audio = screen_share
+        .session
+        .lock()
+        .map_err(|_| "Native screen share lock poisoned".to_string())?
+        .as_ref()
+        .and_then(|session| session.audio_ring.as_ref())
+        .map(|ring| ring.stats());
+    Ok(NativeAudioPipelineStats {
+        microphone,
+        screen_share_audio,
+    })
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .manage(NativeScreenShareManager::default())
         .manage(NativeMicrophoneManager::default())
+        .manage(NativeMicrophoneTestManager::default())
+        .manage(NativePushToTalkManager::default())
+        .manage(GlobalInputManager::default())
+        .manage(NativeRecordingManager::default())
+        .manage(NativeSoundboardManager::default())
+        .manage(NativePlaybackManager::default())
+        .manage(NativeDuckingManager::default())
         .manage(DesktopTrayState::default())
         .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
             show_main_window(app);
@@ -1737,6 +2971,7 @@ pub fn run() {
         .plugin(tauri_plugin_updater::Builder::new().build())
         .plugin(tauri_plugin_opener::init())
         .setup(|app| {
+            start_native_audio_device_watcher(app.handle().clone());
             let open_home =
                 MenuItem::with_id(app, "open_home", "Open Home Server", true, None::<&str>)?;
             let voice_mute = CheckMenuItem::with_id(
@@ -1909,12 +3144,34 @@ pub fn run() {
             set_desktop_tray_home_server,
             set_desktop_tray_update_state,
             list_native_screen_share_sources,
+            native_screen_share_excludes_own_audio,
             start_native_screen_share,
             stop_native_screen_share,
+            native_microphone_supported,
             list_native_audio_input_devices,
+            list_native_audio_output_devices,
             start_native_microphone,
             stop_native_microphone,
             set_native_microphone_muted,
+            set_native_microphone_options,
+            set_native_push_to_talk,
+            start_native_microphone_test,
+            set_native_microphone_test_options,
+            stop_native_microphone_test,
+            get_native_microphone_echo_stats,
+            get_native_audio_pipeline_stats,
+            start_native_recording,
+            stop_native_recording,
+            load_native_soundboard_clip,
+            unload_native_soundboard_clip,
+            play_native_soundboard_clip,
+            set_native_soundboard_clip_volume,
+            stop_native_soundboard,
+            start_native_playback,
+            stop_native_playback,
+            set_native_playback_output_device,
+            set_native_playback_mixes,
+            set_native_ducking,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,353 @@
+use std::collections::HashMap;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc, Arc, Mutex,
+};
+use std::thread::JoinHandle as ThreadJoinHandle;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+use webrtc_audio_processing::Processor as AudioProcessor;
+
+use crate::audio_backend::{open_output_resampled, PlaybackBackend, PlaybackStream, StreamFormat};
+use crate::audio_ring::AudioRing;
+use crate::ducking::{Ducker, Ducking};
+use crate::voice_processing::{SAMPLES_PER_FRAME, SAMPLE_RATE};
+
+/// Remote frames buffered per track, 100 ms. Older frames are dropped when the output device
+/// runs slower than the sender.
+const INPUT_RING_FRAMES: usize = 10;
+/// Frames a track has to have queued before it is played, so network jitter does not turn
+/// into a gap on every frame.
+const PRIME_FRAMES: usize = 2;
+const OUTPUT_CHANNELS: usize = 2;
+
+/// Which of a participant's tracks a mix setting applies to.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum PlaybackSource {
+    #[default]
+    Microphone,
+    ScreenShareAudio,
+}
+
+impl PlaybackSource {
+    /// The serialized name, for use in file names.
+    pub(crate) fn file_name(self) -> &'static str {
+        match self {
+            Self::Microphone => "microphone",
+            Self::ScreenShareAudio => "screen-share-audio",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub(crate) struct ParticipantMix {
+    /// Linear gain, 0 to 2.
+    pub volume: f32,
+    pub muted: bool,
+    /// -1 is hard left, 1 hard right.
+    pub pan: f32,
+}
+
+impl Default for ParticipantMix {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            muted: false,
+            pan: 0.0,
+        }
+    }
+}
+
+impl ParticipantMix {
+    /// Left and right gains. Panning only turns the far side down, so a centred participant
+    /// plays at the set volume on both sides.
+    fn gains(&self) -> (f32, f32) {
+        if self.muted {
+            return (0.0, 0.0);
+        }
+        let volume = self.volume.clamp(0.0, 2.0);
+        let pan = self.pan.clamp(-1.0, 1.0);
+        (volume * (1.0 - pan).min(1.0), volume * (1.0 + pan).min(1.0))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct PlaybackMixEntry {
+    pub identity: String,
+    #[serde(default)]
+    pub source: PlaybackSource,
+    #[serde(flatten)]
+    pub mix: ParticipantMix,
+}
+
+struct PlaybackInput {
+    track_sid: String,
+    identity: String,
+    source: PlaybackSource,
+    ring: Arc<AudioRing>,
+    primed: bool,
+}
+
+/// Mixes the remote tracks of the native room for the output thread. Tracks attach and detach
+/// from the room's event task; the output thread pulls one frame from each every 10 ms.
+#[derive(Default)]
+pub(crate) struct PlaybackMixer {
+    inputs: Mutex<Vec<PlaybackInput>>,
+    mixes: Mutex<HashMap<(String, PlaybackSource), ParticipantMix>>,
+    running: AtomicBool,
+    echo_reference: Mutex<Option<Arc<AudioProcessor>>>,
+}
+
+impl PlaybackMixer {
+    /// Adds a remote mono track and returns the ring its frames go into.
+    pub(crate) fn attach(
+        &self,
+        track_sid: String,
+        identity: String,
+        source: PlaybackSource,
+    ) -> Arc<AudioRing> {
+        let ring = Arc::new(AudioRing::new(SAMPLES_PER_FRAME, INPUT_RING_FRAMES));
+        if let Ok(mut inputs) = self.inputs.lock() {
+            inputs.retain(|input| input.track_sid != track_sid);
+            inputs.push(PlaybackInput {
+                track_sid,
+                identity,
+                source,
+                ring: Arc::clone(&ring),
+                primed: false,
+            });
+        }
+        ring
+    }
+
+    pub(crate) fn detach(&self, track_sid: &str) {
+        if let Ok(mut inputs) = self.inputs.lock() {
+            inputs.retain(|input| {
+                if input.track_sid == track_sid {
+                    input.ring.close();
+                    return false;
+                }
+                true
+            });
+        }
+    }
+
+    pub(crate) fn detach_all(&self) {
+        if let Ok(mut inputs) = self.inputs.lock() {
+            for input in inputs.drain(..) {
+                input.ring.close();
+            }
+        }
+    }
+
+    /// Replaces the mix settings. Tracks without an entry play at unity, centred.
+    pub(crate) fn set_mixes(&self, entries: Vec<PlaybackMixEntry>) {
+        if let Ok(mut mixes) = self.mixes.lock() {
+            *mixes = entries
+                .into_iter()
+                .map(|entry| ((entry.identity, entry.source), entry.mix))
+                .collect();
+        }
+    }
+
+    /// Whether the output thread is playing, in which case the webview is not.
+    pub(crate) fn is_running(&self) -> bool {
+        self.running.load(Ordering::Acquire)
+    }
+
+    /// The echo canceller that should hear everything this mixer plays.
+    pub(crate) fn set_echo_reference(&self, processor: Arc<AudioProcessor>) {
+        if let Ok(mut echo_reference) = self.echo_reference.lock() {
+            *echo_reference = Some(processor);
+        }
+    }
+
+    /// Unregisters `processor`, unless another one has taken its place since.
+    pub(crate) fn clear_echo_reference(&self, processor: &Arc<AudioProcessor>) {
+        if let Ok(mut echo_reference) = self.echo_reference.lock() {
+            if echo_reference
+                .as_ref()
+                .is_some_and(|current| Arc::ptr_eq(current, processor))
+            {
+                *echo_reference = None;
+            }
+        }
+    }
+
+    /// Mixes the next 10 ms of every track into `out`, interleaved stereo. Shared audio goes
+    /// through `ducker`.
+    fn mix_frame(&self, out: &mut [f32], scratch: &mut [f32], ducker: &mut Ducker) {
+        out.fill(0.0);
+        let duck_gains = ducker.next_gains(SAMPLES_PER_FRAME);
+        let mixes = self
+            .mixes
+            .lock()
+            .map(|mixes| mixes.clone())
+            .unwrap_or_default();
+        if let Ok(mut inputs) = self.inputs.lock() {
+            for input in inputs.iter_mut() {
+                if !input.primed {
+                    if input.ring.queued_frames() < PRIME_FRAMES {
+                        continue;
+                    }
+                    input.primed = true;
+                }
+                if !input.ring.pop_frame(scratch) {
+                    input.ring.record_underrun();
+                    input.primed = false;
+                    continue;
+                }
+                let (left, right) = mixes
+                    .get(&(input.identity.clone(), input.source))
+                    .copied()
+                    .unwrap_or_default()
+                    .gains();
+                if input.source == PlaybackSource::ScreenShareAudio {
+                    scratch
+                        .iter_mut()
+                        .zip(duck_gains)
+                        .for_each(|(sample, gain)| *sample *= gain);
+                }
+                for (frame, sample) in out.chunks_exact_mut(OUTPUT_CHANNELS).zip(scratch.iter()) {
+                    frame[0] += sample * left;
+                    frame[1] += sample * right;
+                }
+            }
+        }
+        out.iter_mut()
+            .for_each(|sample| *sample = sample.clamp(-1.0, 1.0));
+
+        let echo_reference = self
+            .echo_reference
+            .lock()
+            .ok()
+            .and_then(|echo_reference| echo_reference.clone());
+        if let Some(processor) = echo_reference {
+            for (mono, frame) in scratch.iter_mut().zip(out.chunks_exact(OUTPUT_CHANNELS)) {
+                *mono = (frame[0] + frame[1]) * 0.5;
+            }
+            let _ = processor.analyze_render_frame([&scratch[..]]);
+        }
+    }
+
+    /// Drops whatever queued up while nothing was playing, so playback starts in sync.
+    fn flush(&self, scratch: &mut [f32]) {
+        if let Ok(mut inputs) = self.inputs.lock() {
+            for input in inputs.iter_mut() {
+                while input.ring.pop_frame(scratch) {}
+                input.primed = false;
+            }
+        }
+    }
+}
+
+pub(crate) struct PlaybackSession {
+    device_tx: watch::Sender<Option<String>>,
+    stop_tx: watch::Sender<bool>,
+    thread: ThreadJoinHandle<()>,
+}
+
+impl PlaybackSession {
+    /// Opens `device_id`, or the default output when it is `None` or unavailable, and plays the
+    /// mixer on it until stopped. The device runs at its own format and the 48 kHz stereo mix is
+    /// converted to it in Rust. The stream is opened on the output thread, which reports back
+    /// whether that worked.
+    pub(crate) fn start(
+        backend: Arc<dyn PlaybackBackend>,
+        mixer: Arc<PlaybackMixer>,
+        ducking: Arc<Ducking>,
+        device_id: Option<String>,
+    ) -> Result<Self, String> {
+        let format = StreamFormat::new(SAMPLE_RATE, OUTPUT_CHANNELS as u16);
+        let (device_tx, mut device_rx) = watch::channel(device_id);
+        let (stop_tx, stop_rx) = watch::channel(false);
+        let (opened_tx, opened_rx) = mpsc::channel::<Result<(), String>>();
+        let thread = std::thread::Builder::new()
+            .name("native-playback".to_string())
+            .spawn(move || {
+                let device_id = device_rx.borrow_and_update().clone();
+                let mut stream =
+                    match open_output_resampled(backend.as_ref(), device_id.as_deref(), format) {
+                        Ok(stream) => stream,
+                        Err(err) => {
+                            let _ = opened_tx.send(Err(err));
+                            return;
+                        }
+                    };
+                let _ = opened_tx.send(Ok(()));
+
+                let mut out = vec![0.0f32; SAMPLES_PER_FRAME * OUTPUT_CHANNELS];
+                let mut scratch = vec![0.0f32; SAMPLES_PER_FRAME];
+                let mut ducker = Ducker::new(ducking, true);
+                mixer.flush(&mut scratch);
+                mixer.running.store(true, Ordering::Release);
+                loop {
+                    if *stop_rx.borrow() {
+                        break;
+                    }
+                    let mut reopen = device_rx.has_changed().unwrap_or(false);
+                    if !reopen {
+                        mixer.mix_frame(&mut out, &mut scratch, &mut ducker);
+                        // Blocks while the device buffer is full, which paces the loop.
+                        if stream.write(&out).is_err() {
+                            std::thread::sleep(Duration::from_millis(500));
+                            reopen = true;
+                        }
+                    }
+                    if reopen {
+                        let device_id = device_rx.borrow_and_update().clone();
+                        drop(stream);
+                        stream = match open_output_or_default(
+                            backend.as_ref(),
+                            device_id.as_deref(),
+                            format,
+                        ) {
+                            Some(stream) => stream,
+                            None => break,
+                        };
+                        mixer.flush(&mut scratch);
+                    }
+                }
+                mixer.running.store(false, Ordering::Release);
+            })
+            .map_err(|err| err.to_string())?;
+
+        let opened = opened_rx
+            .recv_timeout(Duration::from_secs(3))
+            .map_err(|_| "Timed out opening the output device".to_string())
+            .and_then(|opened| opened);
+        if let Err(err) = opened {
+            let _ = stop_tx.send(true);
+            let _ = thread.join();
+            return Err(err);
+        }
+        Ok(Self {
+            device_tx,
+            stop_tx,
+            thread,
+        })
+    }
+
+    pub(crate) fn set_device(&self, device_id: Option<String>) {
+        let _ = self.device_tx.send(device_id);
+    }
+
+    pub(crate) fn stop(self) {
+        let _ = self.stop_tx.send(true);
+        let _ = self.thread.join();
+    }
+}
+
+fn open_output_or_default(
+    backend: &dyn PlaybackBackend,
+    device_id: Option<&str>,
+    format: StreamFormat,
+) -> Option<Box<dyn PlaybackStream>> {
+    open_output_resampled(backend, device_id, format)
+        .or_else(|_| open_output_resampled(backend, None, format))
+        .ok()
+}
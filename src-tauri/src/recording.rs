@@ -0,0 +1,498 @@
+use std::{
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, Weak,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::audio_ring::AudioRing;
+use crate::playback::PlaybackSource;
+use crate::voice_processing::{SAMPLES_PER_FRAME, SAMPLE_RATE};
+
+/// How far a feed may run ahead of the mixer before its oldest audio is dropped.
+const FEED_RING_FRAMES: usize = 50;
+
+type WavFileWriter = hound::WavWriter<BufWriter<File>>;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RecordingOptions {
+    pub directory: PathBuf,
+    /// Also write one WAV per participant next to the mix.
+    #[serde(default)]
+    pub stems: bool,
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RecordingSummary {
+    pub mixed_file: PathBuf,
+    pub sidecar_file: PathBuf,
+    pub duration_ms: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RecordingSidecar {
+    /// Wall-clock start of the recording in milliseconds since the Unix epoch.
+    started_at: u64,
+    duration_ms: u64,
+    sample_rate: u32,
+    mixed_file: String,
+    participants: Vec<SidecarParticipant>,
+}
+
+/// One feed of the recording. A participant who leaves and rejoins shows up once per stay.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SidecarParticipant {
+    identity: String,
+    name: String,
+    local: bool,
+    source: PlaybackSource,
+    stem_file: Option<String>,
+    /// Offsets into the mix, in milliseconds.
+    start_ms: u64,
+    end_ms: u64,
+}
+
+struct TapInner {
+    identity: String,
+    name: String,
+    local: bool,
+    source: PlaybackSource,
+    ring: Mutex<Option<Arc<AudioRing>>>,
+}
+
+/// Where one audio source hands its frames to the recorder. Frames pushed while nothing is
+/// recording are dropped, so sources can hold a tap for as long as they live.
+#[derive(Clone)]
+pub(crate) struct RecordingTap(Arc<TapInner>);
+
+impl RecordingTap {
+    /// Takes interleaved samples at [`SAMPLE_RATE`], downmixes them to mono and queues them in
+    /// whole frames. Never blocks; a frame that races a recording starting or stopping is lost.
+    pub(crate) fn push(&self, samples: &[f32], channels: usize) {
+        let Ok(guard) = self.0.ring.try_lock() else {
+            return;
+        };
+        let Some(ring) = guard.as_ref() else {
+            return;
+        };
+        let channels = channels.max(1);
+        let mut mono = [0.0f32; SAMPLES_PER_FRAME];
+        for chunk in samples.chunks_exact(SAMPLES_PER_FRAME * channels) {
+            for (sample, frame) in mono.iter_mut().zip(chunk.chunks_exact(channels)) {
+                *sample = frame.iter().sum::<f32>() / channels as f32;
+            }
+            ring.push_frame(&mono);
+        }
+    }
+}
+
+struct MixerSource {
+    tap: Weak<TapInner>,
+    identity: String,
+    name: String,
+    local: bool,
+    source: PlaybackSource,
+    ring: Arc<AudioRing>,
+}
+
+struct ActiveRecording {
+    stop: Arc<AtomicBool>,
+    sources: Arc<Mutex<Vec<MixerSource>>>,
+    mixer: JoinHandle<Result<RecordingSummary, String>>,
+}
+
+/// Mixes every registered tap into a WAV file while a recording runs.
+#[derive(Default)]
+pub(crate) struct Recorder {
+    taps: Mutex<Vec<Weak<TapInner>>>,
+    active: Mutex<Option<ActiveRecording>>,
+}
+
+impl Recorder {
+    /// Registers an audio source. It joins a running recording right away and every later one
+    /// until the returned tap and all its clones are dropped.
+    pub(crate) fn tap(
+        &self,
+        identity: String,
+        name: String,
+        local: bool,
+        source: PlaybackSource,
+    ) -> RecordingTap {
+        let tap = Arc::new(TapInner {
+            identity,
+            name,
+            local,
+            source,
+            ring: Mutex::new(None),
+        });
+        if let Ok(mut taps) = self.taps.lock() {
+            taps.retain(|tap| tap.strong_count() > 0);
+            taps.push(Arc::downgrade(&tap));
+        }
+        if let Ok(active) = self.active.lock() {
+            if let Some(active) = active.as_ref() {
+                attach(&tap, &active.sources);
+            }
+        }
+        RecordingTap(tap)
+    }
+
+    pub(crate) fn start(&self, options: RecordingOptions) -> Result<(), String> {
+        let mut active = self
+            .active
+            .lock()
+            .map_err(|_| "Recording lock poisoned".to_string())?;
+        if active.is_some() {
+            return Err("A recording is already running".to_string());
+        }
+        std::fs::create_dir_all(&options.directory).map_err(|err| err.to_string())?;
+
+        let sources = Arc::new(Mutex::new(Vec::new()));
+        let mixer = Mixer::new(options, Arc::clone(&sources))?;
+        if let Ok(taps) = self.taps.lock() {
+            for tap in taps.iter().filter_map(Weak::upgrade) {
+                attach(&tap, &sources);
+            }
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        let mixer_stop = Arc::clone(&stop);
+        let mixer = std::thread::Builder::new()
+            .name("native-recording-mixer".to_string())
+            .spawn(move || mixer.run(&mixer_stop))
+            .map_err(|err| {
+                self.detach_all();
+                err.to_string()
+            })?;
+        *active = Some(ActiveRecording {
+            stop,
+            sources,
+            mixer,
+        });
+        Ok(())
+    }
+
+    /// Stops the running recording, finishes its files and says where they went.
+    pub(crate) fn stop(&self) -> Result<Option<RecordingSummary>, String> {
+        let Some(active) = self
+            .active
+            .lock()
+            .map_err(|_| "Recording lock poisoned".to_string())?
+            .take()
+        else {
+            return Ok(None);
+        };
+        active.stop.store(true, Ordering::SeqCst);
+        let summary = active
+            .mixer
+            .join()
+            .map_err(|_| "Recording mixer panicked".to_string())?;
+        self.detach_all();
+        summary.map(Some)
+    }
+
+    fn detach_all(&self) {
+        if let Ok(taps) = self.taps.lock() {
+            for tap in taps.iter().filter_map(Weak::upgrade) {
+                if let Ok(mut ring) = tap.ring.lock() {
+                    *ring = None;
+                }
+            }
+        }
+    }
+}
+
+fn attach(tap: &Arc<TapInner>, sources: &Mutex<Vec<MixerSource>>) {
+    let ring = Arc::new(AudioRing::new(SAMPLES_PER_FRAME, FEED_RING_FRAMES));
+    let Ok(mut slot) = tap.ring.lock() else {
+        return;
+    };
+    if slot.is_some() {
+        return;
+    }
+    *slot = Some(Arc::clone(&ring));
+    drop(slot);
+    if let Ok(mut sources) = sources.lock() {
+        sources.push(MixerSource {
+            tap: Arc::downgrade(tap),
+            identity: tap.identity.clone(),
+            name: tap.name.clone(),
+            local: tap.local,
+            source: tap.source,
+            ring,
+        });
+    }
+}
+
+struct MixerTrack {
+    source: MixerSource,
+    stem: Option<(String, WavFileWriter)>,
+    start_ms: u64,
+}
+
+/// Pulls one frame from every source each 10 ms and sums them, filling gaps with silence so
+/// all files stay on the same timeline.
+struct Mixer {
+    options: RecordingOptions,
+    sources: Arc<Mutex<Vec<MixerSource>>>,
+    base_name: String,
+    started_at: u64,
+    mix: WavFileWriter,
+    tracks: Vec<MixerTrack>,
+    finished: Vec<SidecarParticipant>,
+    frames: u64,
+    /// Stems opened so far. Part of every stem's name, since two feeds of one participant can
+    /// join in the same frame.
+    stem_count: usize,
+}
+
+impl Mixer {
+    fn new(
+        options: RecordingOptions,
+        sources: Arc<Mutex<Vec<MixerSource>>>,
+    ) -> Result<Self, String> {
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or_default();
+        let base_name = format!("recording-{started_at}");
+        let mix = create_wav(&options.directory.join(format!("{base_name}.wav")))?;
+        Ok(Self {
+            options,
+            sources,
+            base_name,
+            started_at,
+            mix,
+            tracks: Vec::new(),
+            finished: Vec::new(),
+            frames: 0,
+            stem_count: 0,
+        })
+    }
+
+    fn run(mut self, stop: &AtomicBool) -> Result<RecordingSummary, String> {
+        let frame_duration = Duration::from_millis(10);
+        let started = Instant::now();
+        let mut frame = [0.0f32; SAMPLES_PER_FRAME];
+        let mut mixed = [0.0f32; SAMPLES_PER_FRAME];
+        while !stop.load(Ordering::SeqCst) {
+            let deadline = started + frame_duration * (self.frames as u32 + 1);
+            if let Some(wait) = deadline.checked_duration_since(Instant::now()) {
+                std::thread::sleep(wait);
+            }
+
+            self.take_new_sources()?;
+            mixed.fill(0.0);
+            let mut index = 0;
+            while index < self.tracks.len() {
+                let track = &mut self.tracks[index];
+                let has_frame = track.source.ring.pop_frame(&mut frame);
+                if !has_frame && track.source.tap.strong_count() == 0 {
+                    let track = self.tracks.swap_remove(index);
+                    self.finish_track(track)?;
+                    continue;
+                }
+                if !has_frame {
+                    frame.fill(0.0);
+                }
+                for (mixed, sample) in mixed.iter_mut().zip(frame.iter()) {
+                    *mixed += sample;
+                }
+                if let Some((_, stem)) = track.stem.as_mut() {
+                    write_frame(stem, &frame)?;
+                }
+                index += 1;
+            }
+            write_frame(&mut self.mix, &mixed)?;
+            self.frames += 1;
+        }
+
+        for track in std::mem::take(&mut self.tracks) {
+            self.finish_track(track)?;
+        }
+        let sidecar = RecordingSidecar {
+            started_at: self.started_at,
+            duration_ms: self.elapsed_ms(),
+            sample_rate: SAMPLE_RATE,
+            mixed_file: format!("{}.wav", self.base_name),
+            participants: std::mem::take(&mut self.finished),
+        };
+        let mixed_file = self.options.directory.join(&sidecar.mixed_file);
+        let sidecar_file = self
+            .options
+            .directory
+            .join(format!("{}.json", self.base_name));
+        self.mix.finalize().map_err(|err| err.to_string())?;
+
+        let json = serde_json::to_vec_pretty(&sidecar).map_err(|err| err.to_string())?;
+        std::fs::write(&sidecar_file, json).map_err(|err| err.to_string())?;
+        Ok(RecordingSummary {
+            mixed_file,
+            sidecar_file,
+            duration_ms: sidecar.duration_ms,
+        })
+    }
+
+    fn elapsed_ms(&self) -> u64 {
+        self.frames * 10
+    }
+
+    fn take_new_sources(&mut self) -> Result<(), String> {
+        let new_sources = match self.sources.lock() {
+            Ok(mut sources) => std::mem::take(&mut *sources),
+            Err(_) => return Ok(()),
+        };
+        for source in new_sources {
+            let stem = if self.options.stems {
+                self.stem_count += 1;
+                let file = format!(
+                    "{}-{}-{}-{}.wav",
+                    self.base_name,
+                    self.stem_count,
+                    file_safe(&source.identity),
+                    source.source.file_name()
+                );
+                let writer = create_wav(&self.options.directory.join(&file))?;
+                Some((file, writer))
+            } else {
+                None
+            };
+            self.tracks.push(MixerTrack {
+                source,
+                stem,
+                start_ms: self.elapsed_ms(),
+            });
+        }
+        Ok(())
+    }
+
+    fn finish_track(&mut self, track: MixerTrack) -> Result<(), String> {
+        let stem_file = match track.stem {
+            Some((file, writer)) => {
+                writer.finalize().map_err(|err| err.to_string())?;
+                Some(file)
+            }
+            None => None,
+        };
+        self.finished.push(SidecarParticipant {
+            identity: track.source.identity,
+            name: track.source.name,
+            local: track.source.local,
+            source: track.source.source,
+            stem_file,
+            start_ms: track.start_ms,
+            end_ms: self.elapsed_ms(),
+        });
+        Ok(())
+    }
+}
+
+fn create_wav(path: &Path) -> Result<WavFileWriter, String> {
+    hound::WavWriter::create(
+        path,
+        hound::WavSpec {
+            channels: 1,
+            sample_rate: SAMPLE_RATE,
+            bits_per_sample: 16,
+            sample_format: hound::SampleFormat::Int,
+        },
+    )
+    .map_err(|err| err.to_string())
+}
+
+fn write_frame(writer: &mut WavFileWriter, frame: &[f32]) -> Result<(), String> {
+    for sample in frame {
+        writer
+            .write_sample((sample.clamp(-1.0, 1.0) * 32767.0) as i16)
+            .map_err(|err| err.to_string())?;
+    }
+    Ok(())
+}
+
+fn file_safe(identity: &str) -> String {
+    identity
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_feeds_of_one_participant_get_their_own_stems() {
+        let directory =
+            std::env::temp_dir().join(format!("chitchat-{}-recording", std::process::id()));
+        let recorder = Recorder::default();
+        let microphone = recorder.tap(
+            "alice".to_string(),
+            "Alice".to_string(),
+            false,
+            PlaybackSource::Microphone,
+        );
+        let shared = recorder.tap(
+            "alice".to_string(),
+            "Alice".to_string(),
+            false,
+            PlaybackSource::ScreenShareAudio,
+        );
+        recorder
+            .start(RecordingOptions {
+                directory: directory.clone(),
+                stems: true,
+            })
+            .unwrap();
+        for _ in 0..5 {
+            microphone.push(&[0.25; SAMPLES_PER_FRAME], 1);
+            shared.push(&[-0.5; SAMPLES_PER_FRAME], 1);
+        }
+        std::thread::sleep(Duration::from_millis(100));
+        let summary = recorder.stop().unwrap().unwrap();
+
+        let sidecar: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&summary.sidecar_file).unwrap()).unwrap();
+        let participants = sidecar["participants"].as_array().unwrap();
+        let stem = |source: &str| {
+            let participant = participants
+                .iter()
+                .find(|participant| participant["source"] == source)
+                .unwrap();
+            assert_eq!(participant["identity"], "alice");
+            let file = directory.join(participant["stemFile"].as_str().unwrap());
+            hound::WavReader::open(file)
+                .unwrap()
+                .samples::<i16>()
+                .map(|sample| sample.unwrap() as f32 / 32767.0)
+                .find(|sample| *sample != 0.0)
+                .unwrap()
+        };
+        let microphone_sample = stem("microphone");
+        let shared_sample = stem("screen-share-audio");
+        let _ = std::fs::remove_dir_all(&directory);
+
+        assert_eq!(participants.len(), 2);
+        assert_ne!(
+            participants[0]["stemFile"], participants[1]["stemFile"],
+            "both feeds wrote to one stem"
+        );
+        assert!((microphone_sample - 0.25).abs() < 1e-3);
+        assert!((shared_sample + 0.5).abs() < 1e-3);
+    }
+}
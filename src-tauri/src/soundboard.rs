@@ -0,0 +1,392 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc, Mutex,
+    },
+};
+
+use serde::Serialize;
+
+use crate::audio_backend;
+use crate::voice_processing::SAMPLE_RATE;
+
+/// Clips are meant to be short; this keeps a stray album track from filling memory.
+const MAX_CLIP_SECONDS: u64 = 60;
+
+/// A clip decoded and resampled to [`SAMPLE_RATE`], interleaved with one or two channels.
+#[derive(Clone)]
+pub(crate) struct SoundboardClip {
+    samples: Arc<[f32]>,
+    channels: usize,
+}
+
+impl SoundboardClip {
+    /// Decodes a WAV or Ogg Vorbis file, picked by extension.
+    pub(crate) fn load(path: &Path) -> Result<Self, String> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase)
+            .unwrap_or_default();
+        let (samples, sample_rate, channels) = match extension.as_str() {
+            "wav" | "wave" => decode_wav(path)?,
+            "ogg" | "oga" => decode_ogg(path)?,
+            _ => return Err(format!("Unsupported clip format: {}", path.display())),
+        };
+        if channels == 0 || sample_rate == 0 {
+            return Err(format!("{} has no audio", path.display()));
+        }
+
+        // Anything beyond stereo is folded down to mono.
+        let (samples, channels) = if channels > 2 {
+            let mono = samples
+                .chunks_exact(channels)
+                .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+                .collect::<Vec<_>>();
+            (mono, 1)
+        } else {
+            (samples, channels)
+        };
+        let samples = audio_backend::resample_buffer(&samples, channels, sample_rate, SAMPLE_RATE)?;
+        Ok(Self {
+            samples: samples.into(),
+            channels,
+        })
+    }
+
+    pub(crate) fn duration_ms(&self) -> u64 {
+        (self.samples.len() / self.channels) as u64 * 1000 / SAMPLE_RATE as u64
+    }
+}
+
+/// Rejects clips over [`MAX_CLIP_SECONDS`]. Called with the length a file declares before
+/// decoding, and again while decoding in case the declaration was wrong.
+fn check_clip_length(path: &Path, frames: u64, sample_rate: u32) -> Result<(), String> {
+    if frames > MAX_CLIP_SECONDS * sample_rate as u64 {
+        return Err(format!(
+            "{} is longer than {MAX_CLIP_SECONDS} seconds",
+            path.display()
+        ));
+    }
+    Ok(())
+}
+
+fn decode_wav(path: &Path) -> Result<(Vec<f32>, u32, usize), String> {
+    let reader = hound::WavReader::open(path).map_err(|err| err.to_string())?;
+    let spec = reader.spec();
+    check_clip_length(path, reader.duration() as u64, spec.sample_rate)?;
+    let samples = audio_backend::wav_samples(reader)?;
+    Ok((samples, spec.sample_rate, spec.channels as usize))
+}
+
+fn decode_ogg(path: &Path) -> Result<(Vec<f32>, u32, usize), String> {
+    let mut file = File::open(path).map_err(|err| err.to_string())?;
+    let frames = ogg_length_frames(&mut file);
+    file.rewind().map_err(|err| err.to_string())?;
+    let mut reader =
+        lewton::inside_ogg::OggStreamReader::new(file).map_err(|err| err.to_string())?;
+    let sample_rate = reader.ident_hdr.audio_sample_rate;
+    if let Some(frames) = frames {
+        check_clip_length(path, frames, sample_rate)?;
+    }
+    let channels = reader.ident_hdr.audio_channels as usize;
+    let mut samples = Vec::new();
+    while let Some(packet) = reader
+        .read_dec_packet_itl()
+        .map_err(|err| err.to_string())?
+    {
+        samples.extend(packet.iter().map(|sample| *sample as f32 / 32768.0));
+        check_clip_length(path, (samples.len() / channels.max(1)) as u64, sample_rate)?;
+    }
+    Ok((samples, sample_rate, channels))
+}
+
+/// The length of an Ogg Vorbis stream in sample frames, which is the granule position of its
+/// last page. Only the end of the file is read, and the position is left wherever it ends up.
+fn ogg_length_frames<R: Read + Seek>(file: &mut R) -> Option<u64> {
+    const TAIL_BYTES: u64 = 64 * 1024;
+    let len = file.seek(SeekFrom::End(0)).ok()?;
+    file.seek(SeekFrom::Start(len.saturating_sub(TAIL_BYTES)))
+        .ok()?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail).ok()?;
+    // Page header: "OggS", version, header type, then the granule position as a little-endian
+    // 64-bit integer.
+    let page = tail.windows(4).rposition(|window| window == b"OggS")?;
+    let granule = tail.get(page + 6..page + 14)?;
+    let granule = i64::from_le_bytes(granule.try_into().ok()?);
+    u64::try_from(granule).ok()
+}
+
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SoundboardClipFinished {
+    pub play_id: u64,
+    pub clip_id: String,
+    /// Stopped early by a stop-all rather than played to the end.
+    pub interrupted: bool,
+}
+
+enum SoundboardCommand {
+    Play {
+        play_id: u64,
+        clip_id: String,
+        clip: SoundboardClip,
+        volume: f32,
+    },
+    SetVolume {
+        clip_id: String,
+        volume: f32,
+    },
+    StopAll,
+}
+
+struct LoadedClip {
+    clip: SoundboardClip,
+    volume: f32,
+}
+
+/// The loaded clips and the way into whichever microphone session is running.
+#[derive(Default)]
+pub(crate) struct Soundboard {
+    clips: Mutex<HashMap<String, LoadedClip>>,
+    next_play_id: AtomicU64,
+    commands: Mutex<Option<mpsc::Sender<SoundboardCommand>>>,
+}
+
+impl Soundboard {
+    pub(crate) fn insert(&self, clip_id: String, clip: SoundboardClip, volume: f32) {
+        if let Ok(mut clips) = self.clips.lock() {
+            clips.insert(
+                clip_id,
+                LoadedClip {
+                    clip,
+                    volume: volume.clamp(0.0, 2.0),
+                },
+            );
+        }
+    }
+
+    pub(crate) fn remove(&self, clip_id: &str) {
+        if let Ok(mut clips) = self.clips.lock() {
+            clips.remove(clip_id);
+        }
+    }
+
+    /// Starts `clip_id` on the running microphone and returns an id for this playback.
+    pub(crate) fn play(&self, clip_id: &str) -> Result<u64, String> {
+        let (clip, volume) = {
+            let clips = self
+                .clips
+                .lock()
+                .map_err(|_| "Soundboard lock poisoned".to_string())?;
+            let loaded = clips
+                .get(clip_id)
+                .ok_or_else(|| format!("Soundboard clip {clip_id} is not loaded"))?;
+            (loaded.clip.clone(), loaded.volume)
+        };
+        let play_id = self.next_play_id.fetch_add(1, Ordering::Relaxed) + 1;
+        self.send(SoundboardCommand::Play {
+            play_id,
+            clip_id: clip_id.to_string(),
+            clip,
+            volume,
+        })?;
+        Ok(play_id)
+    }
+
+    /// Changes the volume for later plays and for any playback of the clip in progress.
+    pub(crate) fn set_volume(&self, clip_id: &str, volume: f32) -> Result<(), String> {
+        let volume = volume.clamp(0.0, 2.0);
+        {
+            let mut clips = self
+                .clips
+                .lock()
+                .map_err(|_| "Soundboard lock poisoned".to_string())?;
+            let loaded = clips
+                .get_mut(clip_id)
+                .ok_or_else(|| format!("Soundboard clip {clip_id} is not loaded"))?;
+            loaded.volume = volume;
+        }
+        let _ = self.send(SoundboardCommand::SetVolume {
+            clip_id: clip_id.to_string(),
+            volume,
+        });
+        Ok(())
+    }
+
+    pub(crate) fn stop_all(&self) {
+        let _ = self.send(SoundboardCommand::StopAll);
+    }
+
+    /// Hands out the mixer for a new microphone session. Clips played from now on go to it.
+    pub(crate) fn attach(&self) -> SoundboardMixer {
+        let (tx, rx) = mpsc::channel();
+        if let Ok(mut commands) = self.commands.lock() {
+            *commands = Some(tx);
+        }
+        SoundboardMixer {
+            commands: rx,
+            voices: Vec::new(),
+        }
+    }
+
+    fn send(&self, command: SoundboardCommand) -> Result<(), String> {
+        let commands = self
+            .commands
+            .lock()
+            .map_err(|_| "Soundboard lock poisoned".to_string())?;
+        commands
+            .as_ref()
+            .and_then(|commands| commands.send(command).ok())
+            .ok_or_else(|| "The native microphone is not running".to_string())
+    }
+}
+
+struct Voice {
+    play_id: u64,
+    clip_id: String,
+    clip: SoundboardClip,
+    volume: f32,
+    /// Next frame of the clip to play.
+    position: usize,
+}
+
+/// Lives on the capture thread and adds the playing clips to each outgoing frame.
+pub(crate) struct SoundboardMixer {
+    commands: mpsc::Receiver<SoundboardCommand>,
+    voices: Vec<Voice>,
+}
+
+impl SoundboardMixer {
+    /// Mixes the playing clips into `frame`, interleaved with `channels` channels, and reports
+    /// every clip that finished or was stopped.
+    pub(crate) fn mix_into(
+        &mut self,
+        frame: &mut [f32],
+        channels: usize,
+        mut on_finished: impl FnMut(SoundboardClipFinished),
+    ) {
+        while let Ok(command) = self.commands.try_recv() {
+            match command {
+                SoundboardCommand::Play {
+                    play_id,
+                    clip_id,
+                    clip,
+                    volume,
+                } => self.voices.push(Voice {
+                    play_id,
+                    clip_id,
+                    clip,
+                    volume,
+                    position: 0,
+                }),
+                SoundboardCommand::SetVolume { clip_id, volume } => {
+                    for voice in self
+                        .voices
+                        .iter_mut()
+                        .filter(|voice| voice.clip_id == clip_id)
+                    {
+                        voice.volume = volume;
+                    }
+                }
+                SoundboardCommand::StopAll => {
+                    for voice in self.voices.drain(..) {
+                        on_finished(SoundboardClipFinished {
+                            play_id: voice.play_id,
+                            clip_id: voice.clip_id,
+                            interrupted: true,
+                        });
+                    }
+                }
+            }
+        }
+
+        let channels = channels.max(1);
+        self.voices.retain_mut(|voice| {
+            let clip_channels = voice.clip.channels;
+            let clip_frames = voice.clip.samples.len() / clip_channels;
+            for out in frame.chunks_exact_mut(channels) {
+                if voice.position >= clip_frames {
+                    break;
+                }
+                let start = voice.position * clip_channels;
+                let source = &voice.clip.samples[start..start + clip_channels];
+                if clip_channels > channels {
+                    let mixed = source.iter().sum::<f32>() / clip_channels as f32;
+                    out.iter_mut()
+                        .for_each(|sample| *sample += mixed * voice.volume);
+                } else {
+                    for (channel, sample) in out.iter_mut().enumerate() {
+                        *sample += source[channel.min(clip_channels - 1)] * voice.volume;
+                    }
+                }
+                voice.position += 1;
+            }
+            if voice.position < clip_frames {
+                return true;
+            }
+            on_finished(SoundboardClipFinished {
+                play_id: voice.play_id,
+                clip_id: voice.clip_id.clone(),
+                interrupted: false,
+            });
+            false
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::audio_backend::test_support::write_wav;
+
+    /// A clip of constant half-scale mono.
+    fn write_clip(name: &str, sample_rate: u32, seconds: u32) -> PathBuf {
+        let samples = std::iter::repeat_n(i16::MAX / 2, (sample_rate * seconds) as usize);
+        write_wav(name, sample_rate, 1, samples)
+    }
+
+    #[test]
+    fn loads_a_short_wav_clip() {
+        let path = write_clip("short", SAMPLE_RATE, 1);
+        let clip = SoundboardClip::load(&path);
+        let _ = std::fs::remove_file(&path);
+
+        let clip = clip.unwrap();
+        assert_eq!(clip.duration_ms(), 1000);
+        assert!((clip.samples[0] - 0.5).abs() < 1e-3);
+    }
+
+    #[test]
+    fn rejects_a_long_wav_clip() {
+        let path = write_clip("long", 8_000, MAX_CLIP_SECONDS as u32 + 1);
+        let clip = SoundboardClip::load(&path);
+        let _ = std::fs::remove_file(&path);
+
+        assert!(clip.err().unwrap().contains("longer than"));
+    }
+
+    #[test]
+    fn reads_the_ogg_length_from_the_last_page() {
+        fn page(granule: i64) -> Vec<u8> {
+            let mut page = b"OggS\0\x04".to_vec();
+            page.extend_from_slice(&granule.to_le_bytes());
+            page.extend_from_slice(&[0; 16]);
+            page
+        }
+        let mut file = page(0);
+        file.extend_from_slice(&[0x55; 4096]);
+        file.extend(page(1_234_567));
+
+        assert_eq!(ogg_length_frames(&mut Cursor::new(file)), Some(1_234_567));
+        assert_eq!(ogg_length_frames(&mut Cursor::new(vec![0u8; 64])), None);
+    }
+}
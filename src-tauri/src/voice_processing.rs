@@ -0,0 +1,760 @@
+//! The native microphone's per-frame DSP chain: WebRTC audio processing, RNNoise, the
+//! speaking gate and the dynamics stage. It has no device or thread of its own, so the same
+//! chain can run behind the live capture thread or over WAV files (see `examples/process_wav.rs`).
+
+use std::sync::Arc;
+
+use nnnoiseless::DenoiseState;
+
+use crate::dynamics::{DynamicsProcessor, DynamicsSettings};
+use serde::Deserialize;
+use webrtc_audio_processing::{
+    config::{
+        AdaptiveDigital, DownmixMethod, EchoCanceller, FixedDigital, GainController,
+        GainController2, HighPassFilter, NoiseSuppression, NoiseSuppressionLevel, Pipeline,
+    },
+    Config as AudioProcessingConfig, Processor as AudioProcessor,
+};
+
+pub const SAMPLE_RATE: u32 = 48_000;
+pub const SAMPLES_PER_FRAME: usize = 480;
+
+const VAD_VOICE_PROBABILITY: f32 = 0.6;
+
+/// How the gate decides whether we are talking. `Level` only looks at the input RMS; `Vad`
+/// additionally requires RNNoise's voice probability to agree, so loud non-speech such as
+/// keyboards and fans does not open the gate.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum GateMode {
+    #[default]
+    Level,
+    Vad,
+}
+
+/// How a microphone with more than one channel is captured and processed.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChannelMode {
+    /// Average the device's channels to mono as they are captured.
+    #[default]
+    Mono,
+    /// Capture every channel and downmix with [`CaptureDownmix`] before processing.
+    Downmix,
+    /// Capture every channel and run all of them through the audio processing.
+    Multichannel,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum CaptureDownmix {
+    #[default]
+    Average,
+    /// Useful for interfaces with a single mic on input 1 and nothing on the others.
+    FirstChannel,
+}
+
+impl From<CaptureDownmix> for DownmixMethod {
+    fn from(value: CaptureDownmix) -> Self {
+        match value {
+            CaptureDownmix::Average => DownmixMethod::Average,
+            CaptureDownmix::FirstChannel => DownmixMethod::UseFirstChannel,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct VoiceProcessingSettings {
+    #[serde(rename = "noiseSuppressionMode")]
+    pub noise_suppression_mode: String,
+    #[serde(rename = "inputSensitivity")]
+    pub input_sensitivity: f32,
+    #[serde(rename = "echoCancellation", default)]
+    pub echo_cancellation: bool,
+    #[serde(rename = "gateMode", default)]
+    pub gate_mode: GateMode,
+    #[serde(rename = "gateHoldMs", default = "default_gate_hold_ms")]
+    pub gate_hold_ms: u32,
+    #[serde(rename = "gateAttackMs", default = "default_gate_attack_ms")]
+    pub gate_attack_ms: u32,
+    #[serde(rename = "gateReleaseMs", default = "default_gate_release_ms")]
+    pub gate_release_ms: u32,
+    /// Gain applied while the gate is closed, in dB. Anything at or below -90 dB is silence.
+    #[serde(rename = "gateFloorDb", default = "default_gate_floor_db")]
+    pub gate_floor_db: f32,
+    /// How far above the close threshold the level has to rise before the gate opens.
+    #[serde(rename = "gateHysteresis", default = "default_gate_hysteresis")]
+    pub gate_hysteresis: f32,
+    #[serde(rename = "autoGainControl", default)]
+    pub auto_gain_control: bool,
+    /// Speech level the AGC aims for, in dBFS. AGC2 has no target setting, so this becomes its
+    /// headroom below full scale; see [`agc_headroom_db`].
+    #[serde(rename = "agcTargetDbfs", default = "default_agc_target_dbfs")]
+    pub agc_target_dbfs: f32,
+    #[serde(rename = "agcMaxGainDb", default = "default_agc_max_gain_db")]
+    pub agc_max_gain_db: f32,
+    #[serde(rename = "channelMode", default)]
+    pub channel_mode: ChannelMode,
+    #[serde(rename = "downmixMethod", default)]
+    pub downmix_method: CaptureDownmix,
+    /// Close the gate while the keyboard monitor reports a burst of typing.
+    #[serde(rename = "suppressTyping", default)]
+    pub suppress_typing: bool,
+    /// EQ, compressor and limiter applied after the gate.
+    #[serde(default)]
+    pub dynamics: DynamicsSettings,
+}
+
+fn default_gate_hold_ms() -> u32 {
+    180
+}
+
+fn default_gate_attack_ms() -> u32 {
+    5
+}
+
+fn default_gate_release_ms() -> u32 {
+    150
+}
+
+fn default_gate_floor_db() -> f32 {
+    -60.0
+}
+
+fn default_gate_hysteresis() -> f32 {
+    1.2
+}
+
+fn default_agc_target_dbfs() -> f32 {
+    -18.0
+}
+
+fn default_agc_max_gain_db() -> f32 {
+    30.0
+}
+
+impl Default for VoiceProcessingSettings {
+    fn default() -> Self {
+        Self {
+            noise_suppression_mode: "standard".to_string(),
+            input_sensitivity: 0.06,
+            echo_cancellation: false,
+            gate_mode: GateMode::default(),
+            gate_hold_ms: default_gate_hold_ms(),
+            gate_attack_ms: default_gate_attack_ms(),
+            gate_release_ms: default_gate_release_ms(),
+            gate_floor_db: default_gate_floor_db(),
+            gate_hysteresis: default_gate_hysteresis(),
+            auto_gain_control: false,
+            agc_target_dbfs: default_agc_target_dbfs(),
+            agc_max_gain_db: default_agc_max_gain_db(),
+            channel_mode: ChannelMode::default(),
+            downmix_method: CaptureDownmix::default(),
+            suppress_typing: false,
+            dynamics: DynamicsSettings::default(),
+        }
+    }
+}
+
+/// What the chain measured and decided for one frame.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FrameDecision {
+    /// RMS of the unprocessed input.
+    pub rms: f32,
+    /// Peak of the unprocessed input.
+    pub peak: f32,
+    /// RNNoise's voice probability, or the last known one when RNNoise is not running.
+    pub voice_probability: f32,
+    pub gate_open: bool,
+    /// Gate gain at the end of the frame, between the floor and 1.
+    pub gate_gain: f32,
+    /// Estimate of the AGC's gain in dB: the smoothed level change across the whole WebRTC
+    /// stage, so the high-pass filter, echo cancellation and noise suppression pull it down a
+    /// little. The vendored APM does not report the gain AGC2 applies. `None` while the AGC is
+    /// off.
+    pub agc_gain_estimate_db: Option<f32>,
+}
+
+/// Maps the AGC's target speech level in dBFS onto AGC2's adaptive digital headroom. The
+/// adaptive controller brings speech up to `headroom_db` below full scale, so a target of
+/// -18 dBFS is 18 dB of headroom. Targets are kept between -40 and -1 dBFS.
+fn agc_headroom_db(target_dbfs: f32) -> f32 {
+    -target_dbfs.clamp(-40.0, -1.0)
+}
+
+/// Maps the user-facing settings onto the WebRTC audio processing config. "rnnoise" keeps only
+/// the high-pass filter here, RNNoise handles the suppression itself.
+fn audio_processing_config(settings: &VoiceProcessingSettings) -> Option<AudioProcessingConfig> {
+    let mode = settings.noise_suppression_mode.as_str();
+    let level = match mode {
+        "standard" => Some(NoiseSuppressionLevel::Moderate),
+        "aggressive" => Some(NoiseSuppressionLevel::VeryHigh),
+        _ => None,
+    };
+    if level.is_none()
+        && mode != "rnnoise"
+        && !settings.echo_cancellation
+        && !settings.auto_gain_control
+    {
+        return None;
+    }
+    Some(AudioProcessingConfig {
+        pipeline: Pipeline {
+            multi_channel_capture: settings.channel_mode == ChannelMode::Multichannel,
+            capture_downmix_method: settings.downmix_method.into(),
+            ..Pipeline::default()
+        },
+        high_pass_filter: Some(HighPassFilter::default()),
+        echo_canceller: settings.echo_cancellation.then(EchoCanceller::default),
+        noise_suppression: level.map(|level| NoiseSuppression {
+            level,
+            analyze_linear_aec_output: false,
+        }),
+        gain_controller: settings.auto_gain_control.then(|| {
+            // The limiter catches whatever overshoots the headroom.
+            GainController::GainController2(GainController2 {
+                input_volume_controller_enabled: false,
+                adaptive_digital: Some(AdaptiveDigital {
+                    headroom_db: agc_headroom_db(settings.agc_target_dbfs),
+                    max_gain_db: settings.agc_max_gain_db.clamp(0.0, 50.0),
+                    ..AdaptiveDigital::default()
+                }),
+                fixed_digital: FixedDigital::default(),
+            })
+        }),
+        ..AudioProcessingConfig::default()
+    })
+}
+
+/// Frame-in/frame-out voice processing for 10 ms frames at [`SAMPLE_RATE`].
+pub struct VoiceProcessor {
+    audio_processor: Arc<AudioProcessor>,
+    /// One RNNoise instance per processed channel, since each keeps its own noise estimate.
+    denoisers: Vec<Box<DenoiseState<'static>>>,
+    rnnoise_input: [f32; DenoiseState::FRAME_SIZE],
+    rnnoise_output: [f32; DenoiseState::FRAME_SIZE],
+    /// Deinterleaved scratch buffers for multichannel frames.
+    planar: Vec<Vec<f32>>,
+    use_audio_processor: bool,
+    use_rnnoise: bool,
+    use_vad: bool,
+    close_threshold: f32,
+    open_threshold: f32,
+    close_frames: usize,
+    gate_open: bool,
+    below_frames: usize,
+    voice_probability: f32,
+    gate_gain: f32,
+    gate_floor: f32,
+    attack_step: f32,
+    release_step: f32,
+    use_agc: bool,
+    agc_gain_estimate_db: f32,
+    key_pressed: bool,
+    typing: bool,
+    dynamics: DynamicsProcessor,
+    bypass: bool,
+    settings: VoiceProcessingSettings,
+}
+
+impl VoiceProcessor {
+    pub fn new(settings: &VoiceProcessingSettings) -> Result<Self, String> {
+        let audio_processor =
+            Arc::new(AudioProcessor::new(SAMPLE_RATE).map_err(|err| err.to_string())?);
+        let mut processor = Self {
+            audio_processor,
+            denoisers: vec![DenoiseState::new()],
+            rnnoise_input: [0.0; DenoiseState::FRAME_SIZE],
+            rnnoise_output: [0.0; DenoiseState::FRAME_SIZE],
+            planar: Vec::new(),
+            use_audio_processor: false,
+            use_rnnoise: false,
+            use_vad: false,
+            close_threshold: 0.0,
+            open_threshold: 0.0,
+            close_frames: 1,
+            gate_open: true,
+            below_frames: 0,
+            voice_probability: 0.0,
+            gate_gain: 1.0,
+            gate_floor: 0.0,
+            attack_step: 1.0,
+            release_step: 1.0,
+            use_agc: false,
+            agc_gain_estimate_db: 0.0,
+            key_pressed: false,
+            typing: false,
+            dynamics: DynamicsProcessor::new(&settings.dynamics),
+            bypass: false,
+            settings: settings.clone(),
+        };
+        processor.configure(settings);
+        Ok(processor)
+    }
+
+    /// The underlying WebRTC processor, for feeding the echo canceller its render reference
+    /// and for reading stats from other threads.
+    pub fn audio_processor(&self) -> &Arc<AudioProcessor> {
+        &self.audio_processor
+    }
+
+    /// The settings last passed to [`VoiceProcessor::configure`].
+    pub fn settings(&self) -> &VoiceProcessingSettings {
+        &self.settings
+    }
+
+    /// Keeps the gate open and turns off both denoisers regardless of the settings, so music
+    /// and instruments pass through untouched. Echo cancellation, AGC and the dynamics stage
+    /// still follow the settings.
+    pub fn set_bypass(&mut self, bypass: bool) {
+        self.bypass = bypass;
+        let settings = self.settings.clone();
+        self.configure(&settings);
+    }
+
+    /// Keyboard hints for the frames that follow. `key_pressed` lets the WebRTC stage treat
+    /// transients as key clicks; `typing` closes the gate while `suppressTyping` is on.
+    pub fn set_keyboard_activity(&mut self, key_pressed: bool, typing: bool) {
+        self.key_pressed = key_pressed;
+        self.typing = typing;
+    }
+
+    /// Applies new settings without resetting the gate or the denoiser state.
+    pub fn configure(&mut self, settings: &VoiceProcessingSettings) {
+        self.settings = settings.clone();
+        let bypassed;
+        let settings = if self.bypass {
+            bypassed = VoiceProcessingSettings {
+                noise_suppression_mode: "off".to_string(),
+                gate_mode: GateMode::Level,
+                ..settings.clone()
+            };
+            &bypassed
+        } else {
+            settings
+        };
+        self.close_threshold = settings.input_sensitivity.clamp(0.004, 0.12);
+        self.open_threshold = self.close_threshold * settings.gate_hysteresis.clamp(1.0, 4.0);
+        self.close_frames =
+            (settings.gate_hold_ms as usize * SAMPLE_RATE as usize / 1000 / SAMPLES_PER_FRAME)
+                .max(1);
+        self.gate_floor = if settings.gate_floor_db <= -90.0 {
+            0.0
+        } else {
+            10f32.powf(settings.gate_floor_db.min(0.0) / 20.0)
+        };
+        // The envelope ramps linearly between the floor and unity over the configured times.
+        let ramp_step = |ms: u32| {
+            let samples = (ms as f32 * SAMPLE_RATE as f32 / 1000.0).max(1.0);
+            (1.0 - self.gate_floor) / samples
+        };
+        self.attack_step = ramp_step(settings.gate_attack_ms);
+        self.release_step = ramp_step(settings.gate_release_ms);
+        self.use_rnnoise = settings.noise_suppression_mode == "rnnoise";
+        self.use_vad = settings.gate_mode == GateMode::Vad;
+        let config = audio_processing_config(settings);
+        self.use_audio_processor = config.is_some();
+        self.use_agc = settings.auto_gain_control;
+        self.dynamics.configure(&settings.dynamics);
+        self.audio_processor.set_config(config.unwrap_or_default());
+    }
+
+    /// Processes one mono frame of [`SAMPLES_PER_FRAME`] samples in place, including the
+    /// gate's attenuation. Muting is up to the caller.
+    pub fn process_frame(&mut self, frame: &mut [f32]) -> FrameDecision {
+        self.process_planar(&mut [frame])
+    }
+
+    /// Processes one interleaved frame of [`SAMPLES_PER_FRAME`] samples per channel and
+    /// writes the result to `out`, interleaved with `out_channels` channels. Depending on the
+    /// channel mode the input is downmixed first or processed per channel and then mapped onto
+    /// the output layout.
+    pub fn process_interleaved(
+        &mut self,
+        input: &[f32],
+        in_channels: usize,
+        out_channels: usize,
+        out: &mut Vec<f32>,
+    ) -> FrameDecision {
+        let in_channels = in_channels.max(1);
+        let out_channels = out_channels.max(1);
+        let downmix = self.settings.downmix_method;
+        out.clear();
+
+        if in_channels == 1 || self.settings.channel_mode != ChannelMode::Multichannel {
+            let mut mono = input
+                .chunks_exact(in_channels)
+                .map(|frame| downmix_frame(frame, downmix))
+                .collect::<Vec<_>>();
+            let decision = self.process_frame(&mut mono);
+            for sample in mono {
+                out.extend(std::iter::repeat_n(sample, out_channels));
+            }
+            return decision;
+        }
+
+        let mut planar = std::mem::take(&mut self.planar);
+        planar.resize_with(in_channels, Vec::new);
+        for (channel, buffer) in planar.iter_mut().enumerate() {
+            buffer.clear();
+            buffer.extend(input.iter().skip(channel).step_by(in_channels));
+        }
+        let mut channels = planar
+            .iter_mut()
+            .map(|buffer| buffer.as_mut_slice())
+            .collect::<Vec<_>>();
+        let decision = self.process_planar(&mut channels);
+
+        let mut frame = vec![0.0; in_channels];
+        for index in 0..planar[0].len() {
+            if out_channels == 1 {
+                for (channel, buffer) in planar.iter().enumerate() {
+                    frame[channel] = buffer[index];
+                }
+                out.push(downmix_frame(&frame, downmix));
+            } else {
+                for channel in 0..out_channels {
+                    out.push(planar[channel.min(in_channels - 1)][index]);
+                }
+            }
+        }
+        self.planar = planar;
+        decision
+    }
+
+    fn process_planar(&mut self, channels: &mut [&mut [f32]]) -> FrameDecision {
+        let sample_count = channels.iter().map(|channel| channel.len()).sum::<usize>();
+        let rms = planar_rms(channels, sample_count);
+        let peak = channels
+            .iter()
+            .flat_map(|channel| channel.iter())
+            .fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+
+        if self.use_audio_processor {
+            self.audio_processor
+                .set_stream_key_pressed(self.key_pressed);
+            let _ = self
+                .audio_processor
+                .process_capture_frame(channels.iter_mut().map(|channel| &mut **channel));
+        }
+        if self.use_agc && rms >= 0.003 {
+            // The vendored APM does not report the AGC2 gain, so it is estimated from the
+            // level change across the WebRTC stage on frames loud enough to be speech.
+            let output_rms = planar_rms(channels, sample_count);
+            let gain_db = 20.0 * (output_rms.max(1e-6) / rms).log10();
+            self.agc_gain_estimate_db += (gain_db - self.agc_gain_estimate_db) * 0.1;
+        }
+
+        // RNNoise runs on every frame whenever its output or its voice probability is needed,
+        // so its internal state keeps tracking the noise floor.
+        if self.use_rnnoise || self.use_vad {
+            while self.denoisers.len() < channels.len() {
+                self.denoisers.push(DenoiseState::new());
+            }
+            let mut voice_probability = 0.0f32;
+            for (channel, denoiser) in channels.iter_mut().zip(self.denoisers.iter_mut()) {
+                for (index, sample) in channel.iter().enumerate().take(DenoiseState::FRAME_SIZE) {
+                    self.rnnoise_input[index] = sample.clamp(-1.0, 1.0) * 32768.0;
+                }
+                voice_probability = voice_probability
+                    .max(denoiser.process_frame(&mut self.rnnoise_output, &self.rnnoise_input));
+                if self.use_rnnoise {
+                    for (sample, denoised) in channel.iter_mut().zip(self.rnnoise_output.iter()) {
+                        *sample = (denoised / 32768.0).clamp(-1.0, 1.0);
+                    }
+                }
+            }
+            self.voice_probability = voice_probability;
+        }
+
+        if self.bypass {
+            self.gate_open = true;
+            self.below_frames = 0;
+        } else if self.settings.suppress_typing && self.typing {
+            self.gate_open = false;
+            self.below_frames = self.close_frames;
+        } else if self.use_vad {
+            let voice = self.voice_probability >= VAD_VOICE_PROBABILITY;
+            if voice && rms >= self.close_threshold {
+                self.gate_open = true;
+                self.below_frames = 0;
+            } else {
+                self.below_frames = self.below_frames.saturating_add(1);
+                if self.below_frames >= self.close_frames {
+                    self.gate_open = false;
+                }
+            }
+        } else if rms >= self.open_threshold {
+            self.gate_open = true;
+            self.below_frames = 0;
+        } else if rms <= self.close_threshold {
+            self.below_frames = self.below_frames.saturating_add(1);
+            if self.below_frames >= self.close_frames {
+                self.gate_open = false;
+            }
+        } else {
+            self.below_frames = 0;
+        }
+
+        self.apply_gate_envelope(channels);
+        self.dynamics.process(channels);
+
+        FrameDecision {
+            rms,
+            peak,
+            voice_probability: self.voice_probability,
+            gate_open: self.gate_open,
+            gate_gain: self.gate_gain,
+            agc_gain_estimate_db: self.use_agc.then_some(self.agc_gain_estimate_db),
+        }
+    }
+
+    /// Ramps the gate gain towards unity while open and towards the floor while closed,
+    /// so opening and closing never cut the waveform mid-cycle. All channels share one
+    /// envelope.
+    fn apply_gate_envelope(&mut self, channels: &mut [&mut [f32]]) {
+        let (target, step) = if self.gate_open {
+            (1.0, self.attack_step)
+        } else {
+            (self.gate_floor, self.release_step)
+        };
+        if self.gate_gain == target {
+            if target < 1.0 {
+                for channel in channels.iter_mut() {
+                    channel.iter_mut().for_each(|sample| *sample *= target);
+                }
+            }
+            return;
+        }
+        let frame_len = channels
+            .iter()
+            .map(|channel| channel.len())
+            .max()
+            .unwrap_or(0);
+        for index in 0..frame_len {
+            self.gate_gain = if self.gate_gain < target {
+                (self.gate_gain + step).min(target)
+            } else {
+                (self.gate_gain - step).max(target)
+            };
+            for channel in channels.iter_mut() {
+                if let Some(sample) = channel.get_mut(index) {
+                    *sample *= self.gate_gain;
+                }
+            }
+        }
+    }
+}
+
+fn planar_rms(channels: &[&mut [f32]], sample_count: usize) -> f32 {
+    let sum_squares = channels
+        .iter()
+        .flat_map(|channel| channel.iter())
+        .map(|sample| sample * sample)
+        .sum::<f32>();
+    (sum_squares / sample_count.max(1) as f32).sqrt()
+}
+
+fn downmix_frame(frame: &[f32], method: CaptureDownmix) -> f32 {
+    match method {
+        CaptureDownmix::Average => frame.iter().sum::<f32>() / frame.len().max(1) as f32,
+        CaptureDownmix::FirstChannel => frame.first().copied().unwrap_or(0.0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Settings that leave only the gate running, so decisions depend on nothing but the level.
+    fn gate_settings() -> VoiceProcessingSettings {
+        VoiceProcessingSettings {
+            noise_suppression_mode: "off".to_string(),
+            ..VoiceProcessingSettings::default()
+        }
+    }
+
+    fn tone(amplitude: f32) -> Vec<f32> {
+        (0..SAMPLES_PER_FRAME)
+            .map(|index| {
+                let phase = index as f32 * 440.0 / SAMPLE_RATE as f32 * std::f32::consts::TAU;
+                phase.sin() * amplitude
+            })
+            .collect()
+    }
+
+    /// Uniform white noise with the given RMS, from a fixed seed.
+    fn noise(rms: f32, seed: &mut u32) -> Vec<f32> {
+        let amplitude = rms * 3f32.sqrt();
+        (0..SAMPLES_PER_FRAME)
+            .map(|_| {
+                *seed = seed.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+                (*seed as f32 / u32::MAX as f32 * 2.0 - 1.0) * amplitude
+            })
+            .collect()
+    }
+
+    fn decide(processor: &mut VoiceProcessor, mut frame: Vec<f32>) -> FrameDecision {
+        processor.process_frame(&mut frame)
+    }
+
+    #[test]
+    fn tone_opens_the_gate_and_silence_closes_it_after_the_hold() {
+        let settings = gate_settings();
+        let mut processor = VoiceProcessor::new(&settings).unwrap();
+        let hold_frames =
+            settings.gate_hold_ms as usize * SAMPLE_RATE as usize / 1000 / SAMPLES_PER_FRAME;
+
+        for _ in 0..5 {
+            assert!(decide(&mut processor, tone(0.3)).gate_open);
+        }
+        for frame in 1..hold_frames {
+            assert!(
+                decide(&mut processor, vec![0.0; SAMPLES_PER_FRAME]).gate_open,
+                "closed after {frame} silent frames"
+            );
+        }
+        assert!(!decide(&mut processor, vec![0.0; SAMPLES_PER_FRAME]).gate_open);
+        assert!(decide(&mut processor, tone(0.3)).gate_open);
+    }
+
+    #[test]
+    fn noise_below_the_threshold_keeps_the_gate_closed() {
+        let mut processor = VoiceProcessor::new(&gate_settings()).unwrap();
+        let mut seed = 1;
+        let decisions = (0..100)
+            .map(|_| decide(&mut processor, noise(0.02, &mut seed)))
+            .collect::<Vec<_>>();
+
+        assert!(decisions
+            .iter()
+            .all(|decision| (decision.rms - 0.02).abs() < 0.004));
+        assert!(decisions[30..].iter().all(|decision| !decision.gate_open));
+    }
+
+    #[test]
+    fn noise_above_the_threshold_opens_the_gate() {
+        let mut processor = VoiceProcessor::new(&gate_settings()).unwrap();
+        let mut seed = 7;
+        for _ in 0..30 {
+            decide(&mut processor, vec![0.0; SAMPLES_PER_FRAME]);
+        }
+        assert!(decide(&mut processor, noise(0.2, &mut seed)).gate_open);
+    }
+
+    #[test]
+    fn levels_between_the_thresholds_keep_the_gate_as_it_is() {
+        let settings = VoiceProcessingSettings {
+            input_sensitivity: 0.05,
+            gate_hysteresis: 2.0,
+            ..gate_settings()
+        };
+        // RMS 0.07: above the close threshold of 0.05, below the open threshold of 0.1.
+        let between = 0.07 * 2f32.sqrt();
+        let mut processor = VoiceProcessor::new(&settings).unwrap();
+
+        for _ in 0..30 {
+            decide(&mut processor, vec![0.0; SAMPLES_PER_FRAME]);
+        }
+        for _ in 0..50 {
+            assert!(!decide(&mut processor, tone(between)).gate_open);
+        }
+
+        assert!(decide(&mut processor, tone(0.3)).gate_open);
+        for _ in 0..50 {
+            assert!(decide(&mut processor, tone(between)).gate_open);
+        }
+    }
+
+    /// Runs frames of ones through the envelope with the gate held `open` and returns the gain
+    /// applied to every sample.
+    fn envelope(processor: &mut VoiceProcessor, open: bool, frames: usize) -> Vec<f32> {
+        processor.gate_open = open;
+        let mut gains = Vec::new();
+        for _ in 0..frames {
+            let mut frame = vec![1.0; SAMPLES_PER_FRAME];
+            processor.apply_gate_envelope(&mut [&mut frame]);
+            gains.extend(frame);
+        }
+        gains
+    }
+
+    #[test]
+    fn release_ramps_down_to_the_floor_and_stays_there() {
+        let settings = gate_settings();
+        let mut processor = VoiceProcessor::new(&settings).unwrap();
+        let floor = 10f32.powf(settings.gate_floor_db / 20.0);
+        let release_samples = (settings.gate_release_ms * SAMPLE_RATE / 1000) as usize;
+
+        let gains = envelope(
+            &mut processor,
+            false,
+            2 * release_samples / SAMPLES_PER_FRAME,
+        );
+        let reached = gains
+            .iter()
+            .position(|gain| (gain - floor).abs() < 1e-6)
+            .unwrap();
+
+        assert!(
+            reached.abs_diff(release_samples) <= 2,
+            "floor after {reached}"
+        );
+        assert!(gains.windows(2).all(|pair| pair[1] <= pair[0]));
+        assert!(gains.iter().all(|gain| *gain >= floor - 1e-6));
+        assert!(gains[reached..]
+            .iter()
+            .all(|gain| (gain - floor).abs() < 1e-6));
+    }
+
+    #[test]
+    fn attack_returns_to_unity_within_the_attack_time() {
+        let settings = gate_settings();
+        let mut processor = VoiceProcessor::new(&settings).unwrap();
+        let attack_samples = (settings.gate_attack_ms * SAMPLE_RATE / 1000) as usize;
+        envelope(&mut processor, false, 30);
+
+        let gains = envelope(&mut processor, true, 2);
+        let reached = gains.iter().position(|gain| *gain == 1.0).unwrap();
+
+        assert!(reached < attack_samples + 2, "unity after {reached}");
+        assert!(gains.windows(2).all(|pair| pair[1] >= pair[0]));
+        assert!(gains[reached..].iter().all(|gain| *gain == 1.0));
+    }
+
+    #[test]
+    fn floor_at_or_below_minus_90_db_is_silence() {
+        let settings = VoiceProcessingSettings {
+            gate_floor_db: -90.0,
+            ..gate_settings()
+        };
+        let mut processor = VoiceProcessor::new(&settings).unwrap();
+
+        let gains = envelope(&mut processor, false, 30);
+
+        assert_eq!(gains.last(), Some(&0.0));
+        assert!(gains.iter().all(|gain| *gain >= 0.0));
+    }
+
+    #[test]
+    fn agc_target_becomes_the_headroom_below_full_scale() {
+        assert_eq!(agc_headroom_db(-18.0), 18.0);
+        assert_eq!(agc_headroom_db(-3.0), 3.0);
+        assert_eq!(agc_headroom_db(0.0), 1.0);
+        assert_eq!(agc_headroom_db(-60.0), 40.0);
+
+        let settings = VoiceProcessingSettings {
+            auto_gain_control: true,
+            agc_target_dbfs: -24.0,
+            agc_max_gain_db: 20.0,
+            ..gate_settings()
+        };
+        let config = audio_processing_config(&settings).unwrap();
+        let Some(GainController::GainController2(agc)) = config.gain_controller else {
+            panic!("AGC2 not configured");
+        };
+        let adaptive = agc.adaptive_digital.unwrap();
+        assert_eq!(adaptive.headroom_db, 24.0);
+        assert_eq!(adaptive.max_gain_db, 20.0);
+    }
+}
@@ -20,6 +20,8 @@ import {
   Volume2,
   ChevronDown,
   ChevronRight,
+  CircleDot,
+  Music,
 } from "lucide-react";
 import {
   getResolutionsUpTo,
@@ -27,6 +29,7 @@ import {
   getRecommendedScreenShareQuality,
 } from "../../lib/livekit";
 import { getServerUrl } from "../../lib/api";
+import { listenForNativeAudioDevicesChanged } from "../../lib/nativeVoice";
 
 interface SidebarProps {
   rooms: Room[];
@@ -89,6 +92,13 @@ type DeviceOption = {
   label: string;
 };
 
+const SCREEN_SHARE_AUDIO_LABELS: Record<string, string> = {
+  application: " (sharing the window's audio)",
+  "system-excluding-self": " (sharing system audio)",
+  system: " (sharing all audio, including voice chat)",
+  none: "",
+};
+
 export default function Sidebar({
   rooms,
   categories,
@@ -182,6 +192,9 @@ export default function Sidebar({
   const [shareSourcesLoading, setShareSourcesLoading] = useState(false);
   const [sharePickerError, setSharePickerError] = useState<string | null>(null);
   const sharePickerRef = useRef<HTMLDivElement>(null);
+  const [showSoundboard, setShowSoundboard] = useState(false);
+  const soundboardRef = useRef<HTMLDivElement>(null);
+  const [soundboardPos, setSoundboardPos] = useState({ bottom: 0, left: 0 });
   const [pickerLimits, setPickerLimits] = useState<{
     maxScreenShareResolution: string;
     maxScreenShareFps: number;
@@ -280,6 +293,17 @@ export default function Sidebar({
     return () => document.removeEventListener("mousedown", handleClick);
   }, [showSharePicker]);
 
+  useEffect(() => {
+    if (!showSoundboard) return;
+    function handleClick(e: MouseEvent) {
+      if (soundboardRef.current && !soundboardRef.current.contains(e.target as Node)) {
+        setShowSoundboard(false);
+      }
+    }
+    document.addEventListener("mousedown", handleClick);
+    return () => document.removeEventListener("mousedown", handleClick);
+  }, [showSoundboard]);
+
   useEffect(() => {
     if (!contextMenu) return;
     function handleClick(event: MouseEvent) {
@@ -418,6 +442,7 @@ export default function Sidebar({
       let audioOut: DeviceOption[] = [];
       let inputError: string | null = null;
       const nativeInputLoader = voiceControls?.listAudioInputDevices;
+      const nativeOutputLoader = voiceControls?.listAudioOutputDevices;
 
       if (nativeInputLoader) {
         try {
@@ -448,7 +473,16 @@ export default function Sidebar({
         }
       }
 
-      if (navigator.mediaDevices?.enumerateDevices) {
+      if (nativeOutputLoader) {
+        try {
+          audioOut = (await nativeOutputLoader()).map((device) => ({
+            deviceId: device.id,
+            label: device.label,
+          }));
+        } catch {
+          audioOut = [];
+        }
+      } else if (navigator.mediaDevices?.enumerateDevices) {
         try {
           let devices = await navigator.mediaDevices.enumerateDevices();
           audioOut = mapBrowserDevices(devices, "audiooutput");
@@ -481,16 +515,30 @@ export default function Sidebar({
     function onDeviceChange() {
       void loadDevices();
     }
+    let unlistenNative: (() => void) | null = null;
     if (!voiceControls?.listAudioInputDevices) {
       navigator.mediaDevices?.addEventListener?.("devicechange", onDeviceChange);
+    } else {
+      void listenForNativeAudioDevicesChanged(onDeviceChange).then((dispose) => {
+        if (cancelled) {
+          dispose();
+          return;
+        }
+        unlistenNative = dispose;
+      });
     }
     return () => {
       cancelled = true;
+      unlistenNative?.();
       if (!voiceControls?.listAudioInputDevices) {
         navigator.mediaDevices?.removeEventListener?.("devicechange", onDeviceChange);
       }
     };
-  }, [Boolean(voiceControls), Boolean(voiceControls?.listAudioInputDevices)]);
+  }, [
+    Boolean(voiceControls),
+    Boolean(voiceControls?.listAudioInputDevices),
+    Boolean(voiceControls?.listAudioOutputDevices),
+  ]);
 
   const statusMap: Record<string, { label: string; color: string }> = {
     online: { label: "Online", color: "var(--success)" },
@@ -2102,6 +2150,83 @@ export default function Sidebar({
             >
               <Wind size={18} />
             </button>
+            {voiceControls.toggleRecording && (
+              <button
+                onClick={() => {
+                  void voiceControls.toggleRecording?.().catch((err) => {
+                    setDevicePickerError(
+                      err instanceof Error ? err.message : String(err)
+                    );
+                  });
+                }}
+                disabled={!voiceControls.isConnected}
+                className={`sidebar-vc-btn ${voiceControls.isRecording ? "active" : ""}`}
+                title={voiceControls.isRecording ? "Stop recording" : "Record session"}
+              >
+                <CircleDot size={18} />
+              </button>
+            )}
+            {voiceControls.playSoundboardClip && (
+              <div className="share-picker-anchor" ref={soundboardRef}>
+                <button
+                  onClick={() => {
+                    if (!showSoundboard && soundboardRef.current) {
+                      const rect = soundboardRef.current.getBoundingClientRect();
+                      setSoundboardPos({
+                        bottom: window.innerHeight - rect.top + 8,
+                        left: rect.left,
+                      });
+                    }
+                    setShowSoundboard((prev) => !prev);
+                  }}
+                  disabled={!voiceControls.isConnected}
+                  className={`sidebar-vc-btn ${
+                    voiceControls.playingSoundboardClips?.length ? "active" : ""
+                  }`}
+                  title="Soundboard"
+                >
+                  <Music size={18} />
+                </button>
+                {showSoundboard && (
+                  <div
+                    className="share-picker-popover"
+                    style={{ bottom: soundboardPos.bottom, left: soundboardPos.left }}
+                  >
+                    <div className="share-picker-title">Soundboard</div>
+                    {(voiceControls.soundboardClips ?? []).length === 0 && (
+                      <div className="share-picker-label">Add clips in Settings → Voice &amp; Video.</div>
+                    )}
+                    {(voiceControls.soundboardClips ?? []).map((clip) => (
+                      <button
+                        key={clip.id}
+                        type="button"
+                        className="share-picker-start"
+                        style={{ marginTop: 6 }}
+                        onClick={() => {
+                          void voiceControls.playSoundboardClip?.(clip.id).catch((err) => {
+                            setDevicePickerError(
+                              err instanceof Error ? err.message : String(err)
+                            );
+                          });
+                        }}
+                      >
+                        {clip.name}
+                      </button>
+                    ))}
+                    {(voiceControls.playingSoundboardClips ?? []).length > 0 && (
+                      <button
+                        type="button"
+                        className="share-picker-start"
+                        style={{ marginTop: 10 }}
+                        onClick={() => void voiceControls.stopSoundboard?.()}
+                      >
+                        Stop all
+                      </button>
+                    )}
+                  </div>
+                )}
+              </div>
+            )}
             <div className="share-picker-anchor" ref={sharePickerRef}>
               <button
                 onClick={() => {
@@ -2116,7 +2241,11 @@ export default function Sidebar({
                 }}
                 disabled={!voiceControls.isConnected}
                 className={`sidebar-vc-btn ${voiceControls.isScreenSharing ? "active" : ""}`}
-                title={voiceControls.isScreenSharing ? "Stop sharing" : "Share screen"}
+                title={
+                  voiceControls.isScreenSharing
+                    ? `Stop sharing${SCREEN_SHARE_AUDIO_LABELS[voiceControls.screenShareAudioMode ?? "none"]}`
+                    : "Share screen"
+                }
               >
                 <MonitorUp size={18} />
               </button>
@@ -40,12 +40,39 @@ import {
   listNativeScreenShareSources,
   startNativeScreenShare,
   stopNativeScreenShare,
+  type NativeScreenShareAudioMode,
 } from "../../lib/nativeScreenShare";
 import {
   listNativeAudioInputDevices,
+  listNativeAudioOutputDevices,
+  getNativeVoicePreferences,
+  isNativeMicrophoneSupported,
+  loadNativeMicrophoneSupport,
+  listenForNativeMicrophoneDegraded,
+  listenForNativeMicrophoneLevel,
+  listenForNativePushToTalk,
+  listenForNativeSoundboardClipFinished,
+  loadNativeSoundboardClip,
+  playNativeSoundboardClip,
+  setNativeSoundboardClipVolume,
+  stopNativeSoundboard,
+  unloadNativeSoundboardClip,
+  type NativeSoundboardClip,
   setNativeMicrophoneMuted,
+  NATIVE_VOICE_PREFERENCES_EVENT,
+  nativeVoiceOptions,
+  setNativeDucking,
+  setNativeMicrophoneOptions,
+  setNativePlaybackMixes,
+  setNativePlaybackOutputDevice,
+  setNativePushToTalk,
   startNativeMicrophone,
+  startNativePlayback,
+  startNativeRecording,
   stopNativeMicrophone,
+  stopNativePlayback,
+  stopNativeRecording,
+  type NativePlaybackMix,
 } from "../../lib/nativeVoice";
 
 interface VoiceChannelProps {
@@ -115,12 +142,6 @@ function isHiddenCompanionIdentity(identity?: string | null) {
   return isNativeScreenShareIdentity(identity) || isNativeVoiceIdentity(identity);
 }
 
-function supportsNativeMicrophoneCapture() {
-  if (!isProbablyTauri()) return false;
-  if (typeof navigator === "undefined") return false;
-  return /windows/i.test(navigator.userAgent);
-}
-
 function VoiceRoomContent({
   onLeave,
   pushToTalkEnabled,
@@ -197,6 +218,9 @@ function VoiceRoomContent({
   const [manualMute, setManualMute] = useState(false);
   const [deafened, setDeafened] = useState(false);
   const [isScreenSharing, setIsScreenSharing] = useState(false);
+  const [isRecording, setIsRecording] = useState(false);
+  const [playingSoundboard, setPlayingSoundboard] = useState<Record<number, string>>({});
+  const loadedSoundboardRef = useRef<Record<string, NativeSoundboardClip>>({});
   const [isConnected, setIsConnected] = useState(
     room.state === ConnectionState.Connected
   );
@@ -210,13 +234,21 @@ function VoiceRoomContent({
   >({});
   const [screenShareVolumes, setScreenShareVolumes] = useState<Record<string, number>>({});
   const [screenShareMuted, setScreenShareMuted] = useState<Record<string, boolean>>({});
+  const [participantPans, setParticipantPans] = useState<Record<string, number>>({});
   const [screenShareMode, setScreenShareMode] = useState<"browser" | "native" | null>(null);
+  const [screenShareAudioMode, setScreenShareAudioMode] =
+    useState<NativeScreenShareAudioMode | null>(null);
   const backgroundProcessorRef = useRef<ReturnType<typeof BackgroundProcessor> | null>(null);
   const rnnoiseProcessorRef = useRef<LiveKitRnnoiseProcessor | null>(null);
   const rnnoiseAudioContextRef = useRef<AudioContext | null>(null);
 
   const localIdentity = room.localParticipant.identity;
-  const usesNativeMicrophone = supportsNativeMicrophoneCapture();
+  // Loaded by the join handler before the room connects, so this is settled by now.
+  const usesNativeMicrophone = isNativeMicrophoneSupported();
+  const [nativeLocalSpeaking, setNativeLocalSpeaking] = useState(false);
+  const [nativePushToTalkPressed, setNativePushToTalkPressed] = useState(false);
+  const [nativeVoicePrefs, setNativeVoicePrefs] = useState(getNativeVoicePreferences);
+  const usesNativePlayback = usesNativeMicrophone && nativeVoicePrefs.nativePlayback;
   const visibleParticipants = useMemo(() => {
     const deduped = new Map<string, (typeof participants)[number] | typeof localParticipant>();
     participants.forEach((participant) => {
@@ -254,8 +286,96 @@ function VoiceRoomContent({
         next.set(hostIdentity, true);
       }
     });
+    if (nativeLocalSpeaking) {
+      next.set(localIdentity, true);
+    }
     return next;
-  }, [visibleParticipants, nativeVoiceParticipants]);
+  }, [visibleParticipants, nativeVoiceParticipants, nativeLocalSpeaking, localIdentity]);
+
+  useEffect(() => {
+    if (!usesNativeMicrophone) return;
+    let mounted = true;
+    let unlisten: (() => void) | null = null;
+
+    void listenForNativeMicrophoneLevel((level) => {
+      if (!mounted) return;
+      setNativeLocalSpeaking(level.speaking);
+    }).then((dispose) => {
+      if (!mounted) {
+        dispose();
+        return;
+      }
+      unlisten = dispose;
+    });
+
+    return () => {
+      mounted = false;
+      unlisten?.();
+      setNativeLocalSpeaking(false);
+    };
+  }, [usesNativeMicrophone]);
+
+  useEffect(() => {
+    if (!usesNativeMicrophone) return;
+    let mounted = true;
+    let unlisten: (() => void) | null = null;
+
+    void listenForNativeMicrophoneDegraded((status) => {
+      if (!mounted) return;
+      if (status.recovered) {
+        console.warn("Native microphone switched to the default input device:", status.reason);
+        setAudioInputDeviceId("");
+      } else {
+        console.warn("Native microphone input failed:", status.reason);
+      }
+    }).then((dispose) => {
+      if (!mounted) {
+        dispose();
+        return;
+      }
+      unlisten = dispose;
+    });
+
+    return () => {
+      mounted = false;
+      unlisten?.();
+    };
+  }, [usesNativeMicrophone]);
+
+  // The native side keeps the microphone closed while muted or deafened, so the binding stays
+  // registered across those.
+  const usesNativePushToTalk =
+    usesNativeMicrophone && pushToTalkEnabled && room.state === ConnectionState.Connected;
+
+  useEffect(() => {
+    if (!usesNativePushToTalk) return;
+    let mounted = true;
+    let unlisten: (() => void) | null = null;
+
+    void setNativePushToTalk(
+      pushToTalkKey || "Space",
+      nativeVoicePrefs.pushToTalkReleaseDelayMs
+    ).catch((err) => {
+      console.error("Failed to register global push-to-talk", err);
+    });
+    void listenForNativePushToTalk((state) => {
+      if (!mounted) return;
+      setNativePushToTalkPressed(state.pressed);
+    }).then((dispose) => {
+      if (!mounted) {
+        dispose();
+        return;
+      }
+      unlisten = dispose;
+    });
+
+    return () => {
+      mounted = false;
+      unlisten?.();
+      setNativePushToTalkPressed(false);
+      void setNativePushToTalk(null).catch(() => {});
+    };
+  }, [usesNativePushToTalk, pushToTalkKey, nativeVoicePrefs.pushToTalkReleaseDelayMs]);
 
   const formattedKey = useMemo(() => {
     if (!pushToTalkKey) return "Space";
@@ -471,6 +591,25 @@ function VoiceRoomContent({
     micCaptureOptions,
   ]);
 
+  useEffect(() => {
+    function onPreferencesChange() {
+      setNativeVoicePrefs(getNativeVoicePreferences());
+    }
+    window.addEventListener(NATIVE_VOICE_PREFERENCES_EVENT, onPreferencesChange);
+    return () => window.removeEventListener(NATIVE_VOICE_PREFERENCES_EVENT, onPreferencesChange);
+  }, []);
+
+  const nativeProcessingOptions = useMemo(
+    () => ({
+      noiseSuppressionMode: activeNoiseSuppressionMode,
+      inputSensitivity: audioInputSensitivity,
+      ...nativeVoiceOptions(nativeVoicePrefs),
+    }),
+    [activeNoiseSuppressionMode, audioInputSensitivity, nativeVoicePrefs]
+  );
+  const nativeProcessingRef = useRef(nativeProcessingOptions);
+  nativeProcessingRef.current = nativeProcessingOptions;
+
   const restartNativeMicrophone = useCallback(async (requestedDeviceId?: string) => {
     if (!usesNativeMicrophone || room.state !== ConnectionState.Connected || !livekitUrl) {
       return;
@@ -495,9 +634,10 @@ function VoiceRoomContent({
       livekitUrl,
       token: nativeToken.token,
       deviceId: resolvedDeviceId || undefined,
-      noiseSuppressionMode: activeNoiseSuppressionMode,
-      inputSensitivity: audioInputSensitivity,
+      ...nativeProcessingRef.current,
       startMuted: manualMute || deafened || pushToTalkEnabled,
+      qualityProfile: nativeVoicePrefs.qualityProfile,
+      stereo: nativeVoicePrefs.stereo,
     });
 
     setAudioInputDeviceId(resolvedDeviceId);
@@ -508,13 +648,23 @@ function VoiceRoomContent({
     roomId,
     serverUrl,
     authToken,
-    activeNoiseSuppressionMode,
-    audioInputSensitivity,
     manualMute,
     deafened,
     pushToTalkEnabled,
+    nativeVoicePrefs.qualityProfile,
+    nativeVoicePrefs.stereo,
   ]);
 
+  useEffect(() => {
+    if (!usesNativeMicrophone || room.state !== ConnectionState.Connected) return;
+    void setNativeMicrophoneOptions({
+      deviceId: audioInputDeviceId || undefined,
+      ...nativeProcessingOptions,
+    }).catch(() => {
+      // The next session start picks these up if the running one is gone.
+    });
+  }, [usesNativeMicrophone, room.state, audioInputDeviceId, nativeProcessingOptions]);
+
   useEffect(() => {
     if (!usesNativeMicrophone) return;
     if (room.state !== ConnectionState.Connected) {
@@ -541,12 +691,88 @@ function VoiceRoomContent({
 
   useEffect(() => {
     if (!usesNativeMicrophone || room.state !== ConnectionState.Connected) return;
-    void setNativeMicrophoneMuted(
-      manualMute || deafened || pushToTalkEnabled
-    ).catch(() => {
+    void setNativeMicrophoneMuted(manualMute || deafened).catch(() => {
+      // Ignore transient native control failures during reconnects.
+    });
+  }, [usesNativeMicrophone, room.state, manualMute, deafened]);
+
+  useEffect(() => {
+    if (!usesNativeMicrophone || room.state !== ConnectionState.Connected) return;
+    void setNativeDucking(nativeVoicePrefs.ducking).catch(() => {
       // Ignore transient native control failures during reconnects.
     });
-  }, [usesNativeMicrophone, room.state, manualMute, deafened, pushToTalkEnabled]);
+  }, [usesNativeMicrophone, room.state, nativeVoicePrefs.ducking]);
+
+  const audioOutputDeviceRef = useRef(audioOutputDeviceId);
+  audioOutputDeviceRef.current = audioOutputDeviceId;
+
+  useEffect(() => {
+    if (!usesNativePlayback || room.state !== ConnectionState.Connected) return;
+
+    let cancelled = false;
+    void (async () => {
+      const availableDevices = await listNativeAudioOutputDevices().catch(() => []);
+      if (cancelled) return;
+      const requestedDeviceId = audioOutputDeviceRef.current;
+      const resolvedDeviceId = availableDevices.some((device) => device.id === requestedDeviceId)
+        ? requestedDeviceId
+        : "";
+      await startNativePlayback(resolvedDeviceId || undefined);
+    })().catch((err) => {
+      if (cancelled) return;
+      console.error("Failed to start native playback", err);
+    });
+
+    return () => {
+      cancelled = true;
+      void stopNativePlayback().catch(() => {});
+    };
+  }, [usesNativePlayback, room.state]);
+
+  // Native playback hears the native room, where our own webview participant is remote too,
+  // so our own identities are always muted there.
+  useEffect(() => {
+    if (!usesNativePlayback) return;
+
+    const mixes: NativePlaybackMix[] = [
+      { identity: localIdentity, source: "microphone", volume: 0, muted: true, pan: 0 },
+      { identity: localIdentity, source: "screen-share-audio", volume: 0, muted: true, pan: 0 },
+    ];
+    room.remoteParticipants.forEach((participant) => {
+      const micHost = getHostIdentityFromNativeVoiceIdentity(participant.identity);
+      const shareHost = getHostIdentityFromScreenShareIdentity(participant.identity);
+      const isOwn = micHost === localIdentity || shareHost === localIdentity;
+      mixes.push(
+        {
+          identity: participant.identity,
+          source: "microphone",
+          volume: participantVolumes[micHost] ?? 1,
+          muted: isOwn || deafened,
+          pan: participantPans[micHost] ?? 0,
+        },
+        {
+          identity: participant.identity,
+          source: "screen-share-audio",
+          volume: screenShareVolumes[shareHost] ?? 1,
+          muted: isOwn || deafened || Boolean(screenShareMuted[shareHost]),
+          pan: participantPans[shareHost] ?? 0,
+        }
+      );
+    });
+    void setNativePlaybackMixes(mixes).catch(() => {
+      // Ignore transient native control failures during reconnects.
+    });
+  }, [
+    usesNativePlayback,
+    room,
+    participants,
+    localIdentity,
+    deafened,
+    participantVolumes,
+    participantPans,
+    screenShareVolumes,
+    screenShareMuted,
+  ]);
 
   // Apply per-user volume (and deafen override) to all remote audio tracks.
   // Microphone and screen share audio are controlled independently.
@@ -617,6 +843,7 @@ function VoiceRoomContent({
       return changed ? next : prev;
     }
     setParticipantVolumes(pruneRecord);
+    setParticipantPans(pruneRecord);
     setScreenShareVolumes(pruneRecord);
     setScreenShareMuted(pruneRecord);
   }, [remoteParticipants]);
@@ -669,6 +896,8 @@ function VoiceRoomContent({
       if (isTypingTarget(e.target)) return;
       if (e.code === pushToTalkKey || e.key === pushToTalkKey) {
         if (usesNativeMicrophone) {
+          // The global hook drives native push-to-talk, focused or not.
+          if (pushToTalkEnabled) return;
           void setNativeMicrophoneMuted(pushToMuteEnabled);
           return;
         }
@@ -687,7 +916,8 @@ function VoiceRoomContent({
     function onKeyUp(e: KeyboardEvent) {
       if (e.code === pushToTalkKey || e.key === pushToTalkKey) {
         if (usesNativeMicrophone) {
-          void setNativeMicrophoneMuted(pushToTalkEnabled || manualMute || deafened);
+          if (pushToTalkEnabled) return;
+          void setNativeMicrophoneMuted(manualMute || deafened);
           return;
         }
         if (pushToTalkEnabled) {
@@ -907,6 +1137,94 @@ function VoiceRoomContent({
     });
   }, [noiseSuppressionMode]);
 
+  const toggleRecording = useCallback(async () => {
+    if (isRecording) {
+      await stopNativeRecording();
+      setIsRecording(false);
+      return;
+    }
+    const { open } = await import("@tauri-apps/plugin-dialog");
+    const directory = await open({
+      title: "Choose a folder for the recording",
+      directory: true,
+    });
+    if (typeof directory !== "string") return;
+    await startNativeRecording(directory, getNativeVoicePreferences().recordStems);
+    setIsRecording(true);
+  }, [isRecording]);
+
+  // Recording only hears the native room, so it ends with the voice session.
+  useEffect(() => {
+    if (!isRecording || (usesNativeMicrophone && isConnected)) return;
+    void stopNativeRecording().catch(() => {});
+    setIsRecording(false);
+  }, [isRecording, usesNativeMicrophone, isConnected]);
+
+  useEffect(() => {
+    return () => {
+      void stopNativeRecording().catch(() => {});
+    };
+  }, []);
+
+  // Keep the native soundboard in sync with the clips saved in settings. Clips are only
+  // decoded again when their file changes.
+  useEffect(() => {
+    if (!usesNativeMicrophone) return;
+    const loaded = loadedSoundboardRef.current;
+    const clips = nativeVoicePrefs.soundboardClips;
+    for (const id of Object.keys(loaded)) {
+      if (clips.some((clip) => clip.id === id)) continue;
+      delete loaded[id];
+      void unloadNativeSoundboardClip(id).catch(() => {});
+    }
+    for (const clip of clips) {
+      const previous = loaded[clip.id];
+      loaded[clip.id] = clip;
+      if (!previous || previous.path !== clip.path) {
+        void loadNativeSoundboardClip(clip).catch((err) => {
+          delete loaded[clip.id];
+          console.error(`Failed to load soundboard clip ${clip.name}`, err);
+        });
+      } else if (previous.volume !== clip.volume) {
+        void setNativeSoundboardClipVolume(clip.id, clip.volume).catch(() => {});
+      }
+    }
+  }, [usesNativeMicrophone, nativeVoicePrefs.soundboardClips]);
+
+  useEffect(() => {
+    if (!usesNativeMicrophone) return;
+    let mounted = true;
+    let unlisten: (() => void) | null = null;
+    void listenForNativeSoundboardClipFinished(({ playId }) => {
+      if (!mounted) return;
+      setPlayingSoundboard((prev) => {
+        const next = { ...prev };
+        delete next[playId];
+        return next;
+      });
+    }).then((dispose) => {
+      if (!mounted) {
+        dispose();
+        return;
+      }
+      unlisten = dispose;
+    });
+
+    return () => {
+      mounted = false;
+      unlisten?.();
+    };
+  }, [usesNativeMicrophone]);
+
+  const playSoundboardClip = useCallback(async (clipId: string) => {
+    const playId = await playNativeSoundboardClip(clipId);
+    setPlayingSoundboard((prev) => ({ ...prev, [playId]: clipId }));
+  }, []);
+
+  const stopSoundboard = useCallback(async () => {
+    await stopNativeSoundboard();
+  }, []);
+
   const listScreenShareSources = useCallback(async (): Promise<ScreenShareSource[]> => {
     if (!isProbablyTauri()) return [];
     return listNativeScreenShareSources();
@@ -935,13 +1253,15 @@ function VoiceRoomContent({
           serverUrl,
           authToken,
         });
-        await startNativeScreenShare({
+        const started = await startNativeScreenShare({
           livekitUrl,
           token: nativeToken.token,
           source,
           resolution: clampedRes,
           fps: clampedFps,
+          excludeOwnAudio: getNativeVoicePreferences().excludeOwnShareAudio,
         });
+        setScreenShareAudioMode(started.audioMode);
         setScreenShareMode("native");
         setIsScreenSharing(true);
         return;
@@ -1064,7 +1384,10 @@ function VoiceRoomContent({
       setAudioInputDeviceId(deviceId);
       if (usesNativeMicrophone) {
         try {
-          await restartNativeMicrophone(deviceId);
+          await setNativeMicrophoneOptions({
+            deviceId: deviceId || undefined,
+            ...nativeProcessingRef.current,
+          });
         } catch (err) {
           setAudioInputDeviceId(prevDeviceId);
           throw err;
@@ -1088,13 +1411,22 @@ function VoiceRoomContent({
         }
       }
     },
-    [room, audioInputDeviceId, usesNativeMicrophone, restartNativeMicrophone]
+    [room, audioInputDeviceId, usesNativeMicrophone]
   );
 
   const setAudioOutputDevice = useCallback(
     async (deviceId: string) => {
       const prevDeviceId = audioOutputDeviceId;
       setAudioOutputDeviceId(deviceId);
+      if (usesNativePlayback) {
+        try {
+          await setNativePlaybackOutputDevice(deviceId || undefined);
+        } catch (err) {
+          setAudioOutputDeviceId(prevDeviceId);
+          throw err;
+        }
+        return;
+      }
       if (deviceId) {
         try {
           await room.switchActiveDevice("audiooutput", deviceId, true);
@@ -1112,7 +1444,7 @@ function VoiceRoomContent({
         }
       }
     },
-    [room, audioOutputDeviceId]
+    [room, audioOutputDeviceId, usesNativePlayback]
   );
 
   const handleLeave = useCallback(() => {
@@ -1136,8 +1468,17 @@ function VoiceRoomContent({
       isDeafened: deafened,
       isCameraOn: isCameraEnabled ?? false,
       isScreenSharing,
+      screenShareAudioMode: screenShareMode === "native" ? screenShareAudioMode : null,
       isNoiseSuppressionEnabled: noiseSuppressionEnabled,
       usesNativeAudioInput: usesNativeMicrophone,
+      isRecording,
+      toggleRecording: usesNativeMicrophone ? toggleRecording : undefined,
+      soundboardClips: usesNativeMicrophone
+        ? nativeVoicePrefs.soundboardClips.map(({ id, name }) => ({ id, name }))
+        : undefined,
+      playingSoundboardClips: Object.values(playingSoundboard),
+      playSoundboardClip: usesNativeMicrophone ? playSoundboardClip : undefined,
+      stopSoundboard: usesNativeMicrophone ? stopSoundboard : undefined,
       toggleMute,
       toggleDeafen,
       toggleVideo,
@@ -1147,6 +1488,7 @@ function VoiceRoomContent({
       stopScreenShare,
       listScreenShareSources,
       listAudioInputDevices: usesNativeMicrophone ? listNativeAudioInputDevices : undefined,
+      listAudioOutputDevices: usesNativePlayback ? listNativeAudioOutputDevices : undefined,
       setAudioInputDevice,
       setAudioOutputDevice,
       audioInputDeviceId,
@@ -1161,6 +1503,13 @@ function VoiceRoomContent({
         const clamped = Math.min(Math.max(volume, 0), 2);
         setParticipantVolumes((prev) => ({ ...prev, [participantId]: clamped }));
       },
+      participantPans: usesNativePlayback ? participantPans : undefined,
+      setParticipantPan: usesNativePlayback
+        ? (participantId: string, pan: number) => {
+            const clamped = Math.min(Math.max(pan, -1), 1);
+            setParticipantPans((prev) => ({ ...prev, [participantId]: clamped }));
+          }
+        : undefined,
     });
   }, [
     isConnected,
@@ -1168,7 +1517,15 @@ function VoiceRoomContent({
     deafened,
     isCameraEnabled,
     isScreenSharing,
+    screenShareMode,
+    screenShareAudioMode,
     noiseSuppressionEnabled,
+    isRecording,
+    toggleRecording,
+    nativeVoicePrefs.soundboardClips,
+    playingSoundboard,
+    playSoundboardClip,
+    stopSoundboard,
     toggleMute,
     toggleDeafen,
     toggleVideo,
@@ -1178,6 +1535,7 @@ function VoiceRoomContent({
     stopScreenShare,
     listScreenShareSources,
     usesNativeMicrophone,
+    usesNativePlayback,
     setAudioInputDevice,
     setAudioOutputDevice,
     audioInputDeviceId,
@@ -1186,6 +1544,7 @@ function VoiceRoomContent({
     onVoiceControlsChange,
     mediaLimits,
     participantVolumes,
+    participantPans,
     roomId,
   ]);
 
@@ -1276,7 +1635,7 @@ function VoiceRoomContent({
 
   return (
     <div className="voice-room">
-      <RoomAudioRenderer />
+      <RoomAudioRenderer muted={usesNativePlayback} />
       {remoteControlSession && (
         <div className="voice-ptt" style={{ marginBottom: 8 }}>
           Remote control active (
@@ -1371,7 +1730,9 @@ function VoiceRoomContent({
 
 
       {pushToTalkEnabled && (
-        <div className="voice-ptt">Hold {formattedKey} to talk</div>
+        <div className={`voice-ptt${nativePushToTalkPressed ? " active" : ""}`}>
+          Hold {formattedKey} to talk
+        </div>
       )}
       {pushToMuteEnabled && !pushToTalkEnabled && (
         <div className="voice-ptt">Hold {formattedKey} to mute</div>
@@ -1637,7 +1998,7 @@ export default function VoiceChannel({
   const [error, setError] = useState<string | null>(null);
   const autoJoinAttemptedRef = useRef(false);
 
-  const usesNativeMicrophone = supportsNativeMicrophoneCapture();
+  const usesNativeMicrophone = isNativeMicrophoneSupported();
 
   useEffect(() => {
     autoJoinAttemptedRef.current = false;
@@ -1667,6 +2028,9 @@ export default function VoiceChannel({
         serverUrl,
         authToken,
       });
+      // Whether the room publishes the webview's microphone depends on this, so it has to be
+      // known before the token lets the room connect.
+      await loadNativeMicrophoneSupport();
       setToken(result.token);
       setMediaLimits(result.mediaLimits);
     } catch (err) {
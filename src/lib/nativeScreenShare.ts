@@ -7,6 +7,15 @@ export type NativeScreenShareStartOptions = {
   source?: ScreenShareSource;
   resolution: string;
   fps: number;
+  excludeOwnAudio?: boolean;
+};
+
+/** What the screen share's audio track carries. */
+export type NativeScreenShareAudioMode = "application" | "system-excluding-self" | "system";
+
+export type NativeScreenShareStarted = {
+  /** Null when the share has no audio. */
+  audioMode: NativeScreenShareAudioMode | null;
 };
 
 export function isProbablyTauri() {
@@ -16,6 +25,13 @@ export function isProbablyTauri() {
   );
 }
 
+// Only Windows can leave the app's own playback out of shared audio; Linux captures the whole
+// sink monitor.
+export async function canExcludeOwnScreenShareAudio(): Promise<boolean> {
+  if (!isProbablyTauri()) return false;
+  return invoke<boolean>("native_screen_share_excludes_own_audio").catch(() => false);
+}
+
 export async function listNativeScreenShareSources(): Promise<ScreenShareSource[]> {
   if (!isProbablyTauri()) return [];
   return invoke<ScreenShareSource[]>("list_native_screen_share_sources");
@@ -23,11 +39,11 @@ export async function listNativeScreenShareSources(): Promise<ScreenShareSource[
 
 export async function startNativeScreenShare(
   options: NativeScreenShareStartOptions
-): Promise<void> {
+): Promise<NativeScreenShareStarted> {
   if (!isProbablyTauri()) {
     throw new Error("Native screen sharing is unavailable in the browser.");
   }
-  await invoke("start_native_screen_share", { options });
+  return invoke<NativeScreenShareStarted>("start_native_screen_share", { options });
 }
 
 export async function stopNativeScreenShare(): Promise<void> {
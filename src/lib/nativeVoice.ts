@@ -1,21 +1,326 @@
 import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import type { AudioInputDeviceOption } from "../types";
 import { isProbablyTauri } from "./nativeScreenShare";
 
-export type NativeMicrophoneStartOptions = {
-  livekitUrl: string;
-  token: string;
+export type NativeMicrophoneOptions = {
   deviceId?: string;
   noiseSuppressionMode: "off" | "standard" | "aggressive" | "rnnoise";
   inputSensitivity: number;
+  echoCancellation?: boolean;
+  gateMode?: "level" | "vad";
+  gateHoldMs?: number;
+  gateAttackMs?: number;
+  gateReleaseMs?: number;
+  gateFloorDb?: number;
+  gateHysteresis?: number;
+  autoGainControl?: boolean;
+  // Target speech level in dBFS. AGC2 has no target, so the native side uses it as the
+  // headroom below full scale: -18 dBFS means 18 dB of headroom, clamped to -40..-1 dBFS.
+  agcTargetDbfs?: number;
+  agcMaxGainDb?: number;
+  channelMode?: NativeMicrophoneChannelMode;
+  downmixMethod?: "average" | "first-channel";
+  suppressTyping?: boolean;
+  dynamics?: NativeDynamicsSettings;
+};
+
+export type NativeDynamicsSettings = {
+  eq: {
+    lowCutEnabled: boolean;
+    lowCutHz: number;
+    presenceEnabled: boolean;
+    presenceHz: number;
+    presenceGainDb: number;
+    presenceQ: number;
+  };
+  compressor: {
+    enabled: boolean;
+    thresholdDb: number;
+    ratio: number;
+    attackMs: number;
+    releaseMs: number;
+    makeupDb: number;
+  };
+  limiter: {
+    enabled: boolean;
+    ceilingDb: number;
+    releaseMs: number;
+  };
+};
+
+export type NativeDynamicsPreset = {
+  id: string;
+  name: string;
+  settings: NativeDynamicsSettings;
+};
+
+export const DEFAULT_NATIVE_DYNAMICS: NativeDynamicsSettings = {
+  eq: {
+    lowCutEnabled: false,
+    lowCutHz: 80,
+    presenceEnabled: false,
+    presenceHz: 3500,
+    presenceGainDb: 3,
+    presenceQ: 1,
+  },
+  compressor: {
+    enabled: false,
+    thresholdDb: -20,
+    ratio: 3,
+    attackMs: 5,
+    releaseMs: 120,
+    makeupDb: 0,
+  },
+  limiter: {
+    enabled: false,
+    ceilingDb: -1,
+    releaseMs: 50,
+  },
+};
+
+export type NativeDuckingSettings = {
+  enabled: boolean;
+  depthDb: number;
+  thresholdDb: number;
+  attackMs: number;
+  releaseMs: number;
+  holdMs: number;
+  duckPlayback: boolean;
+};
+
+export const DEFAULT_NATIVE_DUCKING: NativeDuckingSettings = {
+  enabled: false,
+  depthDb: 12,
+  thresholdDb: -45,
+  attackMs: 30,
+  releaseMs: 400,
+  holdMs: 250,
+  duckPlayback: false,
+};
+
+export type NativeMicrophoneChannelMode = "mono" | "downmix" | "multichannel";
+
+export type NativeMicrophoneQualityProfile = "voice" | "high-quality-voice" | "music-stereo";
+
+export type NativeMicrophoneStartOptions = NativeMicrophoneOptions & {
+  livekitUrl: string;
+  token: string;
   startMuted: boolean;
+  qualityProfile?: NativeMicrophoneQualityProfile;
+  stereo?: boolean;
+};
+
+export type NativeMicrophoneLevel = {
+  rms: number;
+  peak: number;
+  voiceProbability: number;
+  gateOpen: boolean;
+  speaking: boolean;
+  // Level change across the WebRTC stage while AGC is on. An estimate: noise suppression and
+  // echo cancellation also change the level, and AGC2 does not report its own gain.
+  agcGainEstimateDb: number | null;
+};
+
+export type NativeMicrophoneEchoStats = {
+  echoReturnLoss: number | null;
+  echoReturnLossEnhancement: number | null;
+  divergentFilterFraction: number | null;
+  residualEchoLikelihood: number | null;
+  delayMs: number | null;
+  delayMedianMs: number | null;
+};
+
+export type NativeAudioRingStats = {
+  overruns: number;
+  underruns: number;
+  queuedFrames: number;
+  capacityFrames: number;
+};
+
+export type NativeAudioPipelineStats = {
+  microphone: NativeAudioRingStats | null;
+  screenShareAudio: NativeAudioRingStats | null;
+};
+
+export type NativeSoundboardClip = {
+  id: string;
+  name: string;
+  path: string;
+  volume: number;
+};
+
+export type NativeSoundboardClipFinished = {
+  playId: number;
+  clipId: string;
+  interrupted: boolean;
+};
+
+export type NativeRecordingSummary = {
+  mixedFile: string;
+  sidecarFile: string;
+  durationMs: number;
+};
+
+export type NativeAudioDevicesChanged = {
+  devices: AudioInputDeviceOption[];
+  defaultDeviceId: string | null;
+  added: string[];
+  removed: string[];
+  defaultChanged: boolean;
+};
+
+export type NativeMicrophoneDegraded = {
+  reason: string;
+  requestedDeviceId: string | null;
+  fallbackDeviceId: string | null;
+  recovered: boolean;
+};
+
+export type NativeMicrophoneTestOptions = NativeMicrophoneOptions & {
+  outputDeviceId?: string;
+  recordSeconds?: number;
+};
+
+export type NativeMicrophoneTestState = {
+  phase: "monitoring" | "recording" | "playing" | "finished" | "failed";
+  error: string | null;
+};
+
+export type NativePlaybackSource = "microphone" | "screen-share-audio";
+
+export type NativePlaybackMix = {
+  identity: string;
+  source: NativePlaybackSource;
+  volume: number;
+  muted: boolean;
+  pan: number;
+};
+
+export type NativePushToTalkState = {
+  pressed: boolean;
+};
+
+export type NativeVoicePreferences = {
+  pushToTalkReleaseDelayMs: number;
+  gateMode: "level" | "vad";
+  gateHoldMs: number;
+  gateAttackMs: number;
+  gateReleaseMs: number;
+  gateFloorDb: number;
+  gateHysteresis: number;
+  suppressTyping: boolean;
+  autoGainControl: boolean;
+  agcTargetDbfs: number;
+  agcMaxGainDb: number;
+  qualityProfile: NativeMicrophoneQualityProfile;
+  channelMode: NativeMicrophoneChannelMode;
+  downmixMethod: "average" | "first-channel";
+  stereo: boolean;
+  recordStems: boolean;
+  soundboardClips: NativeSoundboardClip[];
+  dynamics: NativeDynamicsSettings;
+  dynamicsPresets: NativeDynamicsPreset[];
+  excludeOwnShareAudio: boolean;
+  nativePlayback: boolean;
+  ducking: NativeDuckingSettings;
 };
 
+const NATIVE_VOICE_PREFERENCES_KEY = "chitchat-native-voice";
+export const NATIVE_VOICE_PREFERENCES_EVENT = "chitchat-native-voice-preferences";
+
+const DEFAULT_NATIVE_VOICE_PREFERENCES: NativeVoicePreferences = {
+  pushToTalkReleaseDelayMs: 200,
+  gateMode: "level",
+  gateHoldMs: 180,
+  gateAttackMs: 5,
+  gateReleaseMs: 150,
+  gateFloorDb: -60,
+  gateHysteresis: 1.2,
+  suppressTyping: false,
+  autoGainControl: false,
+  agcTargetDbfs: -18,
+  agcMaxGainDb: 30,
+  qualityProfile: "high-quality-voice",
+  channelMode: "mono",
+  downmixMethod: "average",
+  stereo: false,
+  recordStems: false,
+  soundboardClips: [],
+  dynamics: DEFAULT_NATIVE_DYNAMICS,
+  dynamicsPresets: [],
+  excludeOwnShareAudio: true,
+  nativePlayback: false,
+  ducking: DEFAULT_NATIVE_DUCKING,
+};
+
+export function getNativeVoicePreferences(): NativeVoicePreferences {
+  try {
+    const raw = localStorage.getItem(NATIVE_VOICE_PREFERENCES_KEY);
+    if (!raw) return DEFAULT_NATIVE_VOICE_PREFERENCES;
+    return { ...DEFAULT_NATIVE_VOICE_PREFERENCES, ...JSON.parse(raw) };
+  } catch {
+    return DEFAULT_NATIVE_VOICE_PREFERENCES;
+  }
+}
+
+export function updateNativeVoicePreferences(
+  update: Partial<NativeVoicePreferences>
+): NativeVoicePreferences {
+  const next = { ...getNativeVoicePreferences(), ...update };
+  localStorage.setItem(NATIVE_VOICE_PREFERENCES_KEY, JSON.stringify(next));
+  window.dispatchEvent(new Event(NATIVE_VOICE_PREFERENCES_EVENT));
+  return next;
+}
+
+export function nativeVoiceOptions(
+  preferences: NativeVoicePreferences
+): Omit<NativeMicrophoneOptions, "deviceId" | "noiseSuppressionMode" | "inputSensitivity"> {
+  return {
+    gateMode: preferences.gateMode,
+    gateHoldMs: preferences.gateHoldMs,
+    gateAttackMs: preferences.gateAttackMs,
+    gateReleaseMs: preferences.gateReleaseMs,
+    gateFloorDb: preferences.gateFloorDb,
+    gateHysteresis: preferences.gateHysteresis,
+    suppressTyping: preferences.suppressTyping,
+    autoGainControl: preferences.autoGainControl,
+    agcTargetDbfs: preferences.agcTargetDbfs,
+    agcMaxGainDb: preferences.agcMaxGainDb,
+    channelMode: preferences.channelMode,
+    downmixMethod: preferences.downmixMethod,
+    dynamics: preferences.dynamics,
+  };
+}
+
+let nativeMicrophoneSupport: boolean | null = null;
+
+// Asks the native side once whether this build captures the microphone itself. Windows and
+// Linux desktop builds do; macOS and mobile builds leave it to the webview.
+export async function loadNativeMicrophoneSupport(): Promise<boolean> {
+  if (!isProbablyTauri()) return false;
+  if (nativeMicrophoneSupport === null) {
+    nativeMicrophoneSupport = await invoke<boolean>("native_microphone_supported").catch(
+      () => false
+    );
+  }
+  return nativeMicrophoneSupport;
+}
+
+export function isNativeMicrophoneSupported() {
+  return nativeMicrophoneSupport === true;
+}
+
 export async function listNativeAudioInputDevices(): Promise<AudioInputDeviceOption[]> {
   if (!isProbablyTauri()) return [];
   return invoke<AudioInputDeviceOption[]>("list_native_audio_input_devices");
 }
 
+export async function listNativeAudioOutputDevices(): Promise<AudioInputDeviceOption[]> {
+  if (!isProbablyTauri()) return [];
+  return invoke<AudioInputDeviceOption[]>("list_native_audio_output_devices");
+}
+
 export async function startNativeMicrophone(
   options: NativeMicrophoneStartOptions
 ): Promise<void> {
@@ -34,3 +339,177 @@ export async function setNativeMicrophoneMuted(muted: boolean): Promise<void> {
   if (!isProbablyTauri()) return;
   await invoke("set_native_microphone_muted", { muted });
 }
+
+export async function setNativeMicrophoneOptions(
+  options: NativeMicrophoneOptions
+): Promise<void> {
+  if (!isProbablyTauri()) return;
+  await invoke("set_native_microphone_options", { options });
+}
+
+export async function setNativePushToTalk(
+  binding: string | null,
+  releaseDelayMs = getNativeVoicePreferences().pushToTalkReleaseDelayMs
+): Promise<void> {
+  if (!isProbablyTauri()) return;
+  await invoke("set_native_push_to_talk", { binding, releaseDelayMs });
+}
+
+export async function startNativeMicrophoneTest(
+  options: NativeMicrophoneTestOptions
+): Promise<void> {
+  if (!isProbablyTauri()) {
+    throw new Error("Native microphone testing is unavailable in the browser.");
+  }
+  await invoke("start_native_microphone_test", { options });
+}
+
+export async function setNativeMicrophoneTestOptions(
+  options: NativeMicrophoneOptions
+): Promise<void> {
+  if (!isProbablyTauri()) return;
+  await invoke("set_native_microphone_test_options", { options });
+}
+
+export async function stopNativeMicrophoneTest(): Promise<void> {
+  if (!isProbablyTauri()) return;
+  await invoke("stop_native_microphone_test");
+}
+
+export async function getNativeMicrophoneEchoStats(): Promise<NativeMicrophoneEchoStats | null> {
+  if (!isProbablyTauri()) return null;
+  return invoke<NativeMicrophoneEchoStats | null>("get_native_microphone_echo_stats");
+}
+
+export async function getNativeAudioPipelineStats(): Promise<NativeAudioPipelineStats | null> {
+  if (!isProbablyTauri()) return null;
+  return invoke<NativeAudioPipelineStats>("get_native_audio_pipeline_stats");
+}
+
+export async function startNativeRecording(directory: string, stems: boolean): Promise<void> {
+  await invoke("start_native_recording", { options: { directory, stems } });
+}
+
+export async function stopNativeRecording(): Promise<NativeRecordingSummary | null> {
+  if (!isProbablyTauri()) return null;
+  return invoke<NativeRecordingSummary | null>("stop_native_recording");
+}
+
+export async function loadNativeSoundboardClip(clip: NativeSoundboardClip): Promise<void> {
+  if (!isProbablyTauri()) return;
+  await invoke("load_native_soundboard_clip", {
+    clipId: clip.id,
+    path: clip.path,
+    volume: clip.volume,
+  });
+}
+
+export async function unloadNativeSoundboardClip(clipId: string): Promise<void> {
+  if (!isProbablyTauri()) return;
+  await invoke("unload_native_soundboard_clip", { clipId });
+}
+
+export async function playNativeSoundboardClip(clipId: string): Promise<number> {
+  return invoke<number>("play_native_soundboard_clip", { clipId });
+}
+
+export async function setNativeSoundboardClipVolume(clipId: string, volume: number): Promise<void> {
+  if (!isProbablyTauri()) return;
+  await invoke("set_native_soundboard_clip_volume", { clipId, volume });
+}
+
+export async function stopNativeSoundboard(): Promise<void> {
+  if (!isProbablyTauri()) return;
+  await invoke("stop_native_soundboard");
+}
+
+export async function startNativePlayback(outputDeviceId?: string): Promise<void> {
+  if (!isProbablyTauri()) return;
+  await invoke("start_native_playback", { outputDeviceId: outputDeviceId || null });
+}
+
+export async function stopNativePlayback(): Promise<void> {
+  if (!isProbablyTauri()) return;
+  await invoke("stop_native_playback");
+}
+
+export async function setNativePlaybackOutputDevice(outputDeviceId?: string): Promise<void> {
+  if (!isProbablyTauri()) return;
+  await invoke("set_native_playback_output_device", { outputDeviceId: outputDeviceId || null });
+}
+
+export async function setNativePlaybackMixes(mixes: NativePlaybackMix[]): Promise<void> {
+  if (!isProbablyTauri()) return;
+  await invoke("set_native_playback_mixes", { mixes });
+}
+
+export async function setNativeDucking(settings: NativeDuckingSettings): Promise<void> {
+  if (!isProbablyTauri()) return;
+  await invoke("set_native_ducking", { settings });
+}
+
+export async function listenForNativeSoundboardClipFinished(
+  handler: (finished: NativeSoundboardClipFinished) => void
+): Promise<UnlistenFn> {
+  if (!isProbablyTauri()) {
+    return () => {};
+  }
+  return listen<NativeSoundboardClipFinished>("native-soundboard-clip-finished", (event) => {
+    handler(event.payload);
+  });
+}
+
+export async function listenForNativeMicrophoneLevel(
+  handler: (level: NativeMicrophoneLevel) => void
+): Promise<UnlistenFn> {
+  if (!isProbablyTauri()) {
+    return () => {};
+  }
+  return listen<NativeMicrophoneLevel>("native-microphone-level", (event) => {
+    handler(event.payload);
+  });
+}
+
+export async function listenForNativeAudioDevicesChanged(
+  handler: (change: NativeAudioDevicesChanged) => void
+): Promise<UnlistenFn> {
+  if (!isProbablyTauri()) {
+    return () => {};
+  }
+  return listen<NativeAudioDevicesChanged>("native-audio-devices-changed", (event) => {
+    handler(event.payload);
+  });
+}
+
+export async function listenForNativeMicrophoneDegraded(
+  handler: (status: NativeMicrophoneDegraded) => void
+): Promise<UnlistenFn> {
+  if (!isProbablyTauri()) {
+    return () => {};
+  }
+  return listen<NativeMicrophoneDegraded>("native-microphone-degraded", (event) => {
+    handler(event.payload);
+  });
+}
+
+export async function listenForNativePushToTalk(
+  handler: (state: NativePushToTalkState) => void
+): Promise<UnlistenFn> {
+  if (!isProbablyTauri()) {
+    return () => {};
+  }
+  return listen<NativePushToTalkState>("native-push-to-talk", (event) => {
+    handler(event.payload);
+  });
+}
+
+export async function listenForNativeMicrophoneTestState(
+  handler: (state: NativeMicrophoneTestState) => void
+): Promise<UnlistenFn> {
+  if (!isProbablyTauri()) {
+    return () => {};
+  }
+  return listen<NativeMicrophoneTestState>("native-microphone-test-state", (event) => {
+    handler(event.payload);
+  });
+}
@@ -2267,6 +2267,9 @@ export default function Home() {
         const isCallOwner = activeCall?.ownerUserId === user?.id;
         const inActiveCall = activeCall?.participantIds.includes(voiceSidebarCtxMenu.participantId);
         const vol = voiceControls?.participantVolumes[voiceSidebarCtxMenu.participantId] ?? 1;
+        const pan = voiceControls?.participantPans?.[voiceSidebarCtxMenu.participantId] ?? 0;
+        const panLabel =
+          Math.abs(pan) < 0.005 ? "C" : `${pan < 0 ? "L" : "R"}${Math.round(Math.abs(pan) * 100)}`;
         return (
           <div
             ref={voiceSidebarCtxMenuRef}
@@ -2339,6 +2342,29 @@ export default function Home() {
                 <span className="voice-ctx-volume-value">{Math.round(vol * 100)}%</span>
               </div>
             )}
+            {voiceControls?.setParticipantPan && !isSelf && (
+              <div className="voice-ctx-volume-row">
+                <span className="voice-ctx-volume-label">Pan</span>
+                <input
+                  type="range"
+                  min={-100}
+                  max={100}
+                  step={1}
+                  className="voice-ctx-volume-slider"
+                  value={Math.round(pan * 100)}
+                  onChange={(e) =>
+                    voiceControls.setParticipantPan?.(
+                      voiceSidebarCtxMenu.participantId,
+                      Number(e.target.value) / 100
+                    )
+                  }
+                  onDoubleClick={() =>
+                    voiceControls.setParticipantPan?.(voiceSidebarCtxMenu.participantId, 0)
+                  }
+                />
+                <span className="voice-ctx-volume-value">{panLabel}</span>
+              </div>
+            )}
             {ctxUser && !isSelf && (canKickMembers || canBanMembers || canTimeoutMembers || canModerateVoice) && (
               <>
                 <div className="member-context-menu-note" style={{ marginTop: 4 }}>Moderation</div>
@@ -7,6 +7,26 @@ import {
   getDesktopNotificationPermission,
   requestDesktopNotificationPermission as requestNativeDesktopNotificationPermission,
 } from "../lib/desktopNotifications";
+import {
+  getNativeVoicePreferences,
+  listNativeAudioOutputDevices,
+  listenForNativeMicrophoneLevel,
+  listenForNativeMicrophoneTestState,
+  nativeVoiceOptions,
+  setNativeMicrophoneTestOptions,
+  startNativeMicrophoneTest,
+  stopNativeMicrophoneTest,
+  updateNativeVoicePreferences,
+  type NativeMicrophoneLevel,
+  type NativeMicrophoneChannelMode,
+  type NativeMicrophoneQualityProfile,
+  type NativeDynamicsSettings,
+  type NativeDuckingSettings,
+  type NativeMicrophoneTestState,
+  type NativeSoundboardClip,
+} from "../lib/nativeVoice";
+import type { AudioInputDeviceOption } from "../types";
+import { canExcludeOwnScreenShareAudio, isProbablyTauri } from "../lib/nativeScreenShare";
 
 const THEMES = [
   { id: "midnight", label: "Midnight", accent: "#7c6aff", bg: "#0f0f17" },
@@ -45,6 +65,14 @@ const STATUS_STYLES: Record<
   offline: { color: "var(--text-muted)", glow: "rgba(148,163,184,0.35)" },
 };
 
+// DOM `MouseEvent.button` numbers to the binding codes the native input hook parses. Primary
+// and secondary clicks are left out so they stay usable in the settings page.
+const MOUSE_BUTTON_CODES: Record<number, string> = {
+  1: "Mouse3",
+  3: "Mouse4",
+  4: "Mouse5",
+};
+
 type SettingsProps = {
   onClose?: () => void;
   soundEnabled?: boolean;
@@ -93,6 +121,14 @@ export default function Settings({
     NotificationPermission | "unsupported"
   >("default");
   const [capturingKey, setCapturingKey] = useState(false);
+  const [nativeMicLevel, setNativeMicLevel] = useState<NativeMicrophoneLevel | null>(null);
+  const [nativeVoicePrefs, setNativeVoicePrefs] = useState(getNativeVoicePreferences);
+  const [nativeOutputs, setNativeOutputs] = useState<AudioInputDeviceOption[]>([]);
+  const [canExcludeOwnShareAudio, setCanExcludeOwnShareAudio] = useState(false);
+  const [nativeTestOutputId, setNativeTestOutputId] = useState("");
+  const [nativeTestRecord, setNativeTestRecord] = useState(false);
+  const [dynamicsPresetName, setDynamicsPresetName] = useState("");
+  const [nativeTestState, setNativeTestState] = useState<NativeMicrophoneTestState | null>(null);
   const [activeTheme, setActiveTheme] = useState(getTheme);
   const [activeTab, setActiveTab] = useState<"settings" | "public-profile">(
     "settings"
@@ -138,6 +174,77 @@ export default function Settings({
     setTwoFactorEnabled(profile.twoFactorEnabled);
   }, [profile.twoFactorEnabled]);
 
+  useEffect(() => {
+    let mounted = true;
+    let unlisten: (() => void) | null = null;
+
+    void listenForNativeMicrophoneLevel((level) => {
+      if (!mounted) return;
+      setNativeMicLevel(level);
+    }).then((dispose) => {
+      if (!mounted) {
+        dispose();
+        return;
+      }
+      unlisten = dispose;
+    });
+
+    return () => {
+      mounted = false;
+      unlisten?.();
+    };
+  }, []);
+
+  useEffect(() => {
+    if (!isProbablyTauri()) return;
+    let mounted = true;
+    let unlisten: (() => void) | null = null;
+
+    void listNativeAudioOutputDevices()
+      .then((devices) => {
+        if (mounted) setNativeOutputs(devices);
+      })
+      .catch(() => {});
+    void canExcludeOwnScreenShareAudio().then((supported) => {
+      if (mounted) setCanExcludeOwnShareAudio(supported);
+    });
+    void listenForNativeMicrophoneTestState((state) => {
+      if (!mounted) return;
+      setNativeTestState(state);
+    }).then((dispose) => {
+      if (!mounted) {
+        dispose();
+        return;
+      }
+      unlisten = dispose;
+    });
+
+    return () => {
+      mounted = false;
+      unlisten?.();
+      void stopNativeMicrophoneTest().catch(() => {});
+    };
+  }, []);
+
+  const nativeTestRunning =
+    nativeTestState !== null &&
+    nativeTestState.phase !== "finished" &&
+    nativeTestState.phase !== "failed";
+
+  useEffect(() => {
+    if (!nativeTestRunning) return;
+    void setNativeMicrophoneTestOptions({
+      noiseSuppressionMode: form.noiseSuppressionMode,
+      inputSensitivity: form.audioInputSensitivity,
+      ...nativeVoiceOptions(nativeVoicePrefs),
+    }).catch(() => {});
+  }, [
+    nativeTestRunning,
+    form.noiseSuppressionMode,
+    form.audioInputSensitivity,
+    nativeVoicePrefs,
+  ]);
+
   useEffect(() => {
     setForm({
       username: profile.username,
@@ -458,8 +565,26 @@ export default function Settings({
       setCapturingKey(false);
     }
 
+    // Middle and side mouse buttons can only be bound for the desktop app's global hook.
+    function onMouseDown(e: MouseEvent) {
+      const code = MOUSE_BUTTON_CODES[e.button];
+      if (!code) return;
+      e.preventDefault();
+      setForm((prev) => ({
+        ...prev,
+        pushToTalkKey: code,
+      }));
+      setCapturingKey(false);
+    }
+
     window.addEventListener("keydown", onKeyDown);
-    return () => window.removeEventListener("keydown", onKeyDown);
+    if (isProbablyTauri()) {
+      window.addEventListener("mousedown", onMouseDown);
+    }
+    return () => {
+      window.removeEventListener("keydown", onKeyDown);
+      window.removeEventListener("mousedown", onMouseDown);
+    };
   }, [capturingKey]);
 
   function formatKey(value: string) {
@@ -470,6 +595,89 @@ export default function Settings({
     return value;
   }
 
+  async function startNativeTest() {
+    try {
+      setNativeTestState(null);
+      await startNativeMicrophoneTest({
+        outputDeviceId: nativeTestOutputId || undefined,
+        recordSeconds: nativeTestRecord ? 5 : undefined,
+        noiseSuppressionMode: form.noiseSuppressionMode,
+        inputSensitivity: form.audioInputSensitivity,
+        ...nativeVoiceOptions(nativeVoicePrefs),
+      });
+    } catch (err) {
+      setNativeTestState({
+        phase: "failed",
+        error: err instanceof Error ? err.message : String(err),
+      });
+    }
+  }
+
+  async function stopNativeTest() {
+    await stopNativeMicrophoneTest().catch(() => {});
+    setNativeTestState(null);
+  }
+
+  async function addSoundboardClip() {
+    const { open } = await import("@tauri-apps/plugin-dialog");
+    const path = await open({
+      title: "Add soundboard clip",
+      filters: [{ name: "Audio", extensions: ["wav", "ogg"] }],
+    });
+    if (typeof path !== "string") return;
+    const name = path.split(/[\\/]/).pop()?.replace(/\.[^.]+$/, "") || "Clip";
+    const clip: NativeSoundboardClip = {
+      id: `${Date.now().toString(36)}-${Math.random().toString(36).slice(2, 8)}`,
+      name,
+      path,
+      volume: 1,
+    };
+    setNativeVoicePrefs(
+      updateNativeVoicePreferences({
+        soundboardClips: [...nativeVoicePrefs.soundboardClips, clip],
+      })
+    );
+  }
+
+  function updateDynamics<K extends keyof NativeDynamicsSettings>(
+    section: K,
+    update: Partial<NativeDynamicsSettings[K]>
+  ) {
+    const dynamics = nativeVoicePrefs.dynamics;
+    setNativeVoicePrefs(
+      updateNativeVoicePreferences({
+        dynamics: { ...dynamics, [section]: { ...dynamics[section], ...update } },
+      })
+    );
+  }
+
+  function updateDucking(update: Partial<NativeDuckingSettings>) {
+    setNativeVoicePrefs(
+      updateNativeVoicePreferences({
+        ducking: { ...nativeVoicePrefs.ducking, ...update },
+      })
+    );
+  }
+
+  function saveDynamicsPreset() {
+    const name = dynamicsPresetName.trim();
+    if (!name) return;
+    const existing = nativeVoicePrefs.dynamicsPresets.find((preset) => preset.name === name);
+    const preset = {
+      id: existing?.id ?? `${Date.now().toString(36)}-${Math.random().toString(36).slice(2, 8)}`,
+      name,
+      settings: nativeVoicePrefs.dynamics,
+    };
+    setNativeVoicePrefs(
+      updateNativeVoicePreferences({
+        dynamicsPresets: existing
+          ? nativeVoicePrefs.dynamicsPresets.map((item) => (item.id === existing.id ? preset : item))
+          : [...nativeVoicePrefs.dynamicsPresets, preset],
+      })
+    );
+    setDynamicsPresetName("");
+  }
+
   async function playSpeakerTest() {
     try {
       const ctx = new AudioContext();
@@ -1067,6 +1275,28 @@ export default function Settings({
                           : formatKey(form.pushToTalkKey)}
                       </button>
                     </div>
+                    {isProbablyTauri() && (
+                      <div>
+                        <label className="profile-label">
+                          PTT release delay ({nativeVoicePrefs.pushToTalkReleaseDelayMs} ms)
+                        </label>
+                        <input
+                          type="range"
+                          min={0}
+                          max={1000}
+                          step={50}
+                          value={nativeVoicePrefs.pushToTalkReleaseDelayMs}
+                          onChange={(e) =>
+                            setNativeVoicePrefs(
+                              updateNativeVoicePreferences({
+                                pushToTalkReleaseDelayMs: Number(e.target.value),
+                              })
+                            )
+                          }
+                          className="voice-mix-slider"
+                        />
+                      </div>
+                    )}
                   </div>
                   <div style={{ marginTop: 12 }}>
                     <label className="profile-label">
@@ -1086,6 +1316,27 @@ export default function Settings({
                       }
                       className="voice-mix-slider"
                     />
+                    {nativeMicLevel && (
+                      <div
+                        className={`voice-level-meter${nativeMicLevel.gateOpen ? " open" : ""}`}
+                      >
+                        <div
+                          className="voice-level-meter-fill"
+                          style={{ width: `${Math.min(100, nativeMicLevel.rms * 100)}%` }}
+                        />
+                        <div
+                          className="voice-level-meter-threshold"
+                          style={{ left: `${Math.round(form.audioInputSensitivity * 100)}%` }}
+                        />
+                      </div>
+                    )}
+                    {nativeMicLevel?.agcGainEstimateDb != null && (
+                      <div className="profile-help">
+                        Automatic gain (estimated):{" "}
+                        {nativeMicLevel.agcGainEstimateDb >= 0 ? "+" : ""}
+                        {nativeMicLevel.agcGainEstimateDb.toFixed(1)} dB
+                      </div>
+                    )}
                     <div className="profile-help">
                       Higher values require a louder voice before the mic opens. New profiles default to 6%.
                     </div>
@@ -1115,6 +1366,252 @@ export default function Settings({
                       Balanced uses the browser voice stack. Focused Voice narrows the mic to mono. RNNoise is strongest, but can sound over-processed on some mics.
                     </div>
                   </div>
+                  {isProbablyTauri() && (
+                    <div style={{ marginTop: 12 }}>
+                      <label className="profile-label">Microphone quality</label>
+                      <select
+                        className="profile-select"
+                        value={nativeVoicePrefs.qualityProfile}
+                        onChange={(e) =>
+                          setNativeVoicePrefs(
+                            updateNativeVoicePreferences({
+                              qualityProfile: e.target.value as NativeMicrophoneQualityProfile,
+                            })
+                          )
+                        }
+                      >
+                        <option value="voice">Voice (low bandwidth)</option>
+                        <option value="high-quality-voice">High quality voice</option>
+                        <option value="music-stereo">Music (stereo)</option>
+                      </select>
+                      <div className="profile-help">
+                        Music sends stereo at a high bitrate and skips voice detection and noise suppression, so instruments come through untouched.
+                      </div>
+                      <label className="profile-label" style={{ marginTop: 8 }}>
+                        Input channels
+                      </label>
+                      <select
+                        className="profile-select"
+                        value={nativeVoicePrefs.channelMode}
+                        onChange={(e) =>
+                          setNativeVoicePrefs(
+                            updateNativeVoicePreferences({
+                              channelMode: e.target.value as NativeMicrophoneChannelMode,
+                            })
+                          )
+                        }
+                      >
+                        <option value="mono">Mono (system mix)</option>
+                        <option value="downmix">All channels, mixed to mono</option>
+                        <option value="multichannel">All channels, processed separately</option>
+                      </select>
+                      {nativeVoicePrefs.channelMode !== "mono" && (
+                        <select
+                          className="profile-select"
+                          style={{ marginTop: 8 }}
+                          value={nativeVoicePrefs.downmixMethod}
+                          onChange={(e) =>
+                            setNativeVoicePrefs(
+                              updateNativeVoicePreferences({
+                                downmixMethod: e.target.value as "average" | "first-channel",
+                              })
+                            )
+                          }
+                        >
+                          <option value="average">Average all channels</option>
+                          <option value="first-channel">Use the first channel</option>
+                        </select>
+                      )}
+                      <label
+                        className="profile-label"
+                        style={{ marginTop: 8, display: "flex", alignItems: "center", gap: 8 }}
+                      >
+                        <input
+                          type="checkbox"
+                          checked={nativeVoicePrefs.stereo || nativeVoicePrefs.qualityProfile === "music-stereo"}
+                          disabled={nativeVoicePrefs.qualityProfile === "music-stereo"}
+                          onChange={(e) =>
+                            setNativeVoicePrefs(
+                              updateNativeVoicePreferences({ stereo: e.target.checked })
+                            )
+                          }
+                        />
+                        Send stereo
+                      </label>
+                      <label
+                        className="profile-label"
+                        style={{ marginTop: 8, display: "flex", alignItems: "center", gap: 8 }}
+                      >
+                        <input
+                          type="checkbox"
+                          checked={nativeVoicePrefs.recordStems}
+                          onChange={(e) =>
+                            setNativeVoicePrefs(
+                              updateNativeVoicePreferences({ recordStems: e.target.checked })
+                            )
+                          }
+                        />
+                        Save a separate track per participant when recording
+                      </label>
+                    </div>
+                  )}
+                  {isProbablyTauri() && (
+                    <div style={{ marginTop: 12 }}>
+                      <label className="profile-label">Voice detection</label>
+                      <select
+                        className="profile-select"
+                        value={nativeVoicePrefs.gateMode}
+                        onChange={(e) =>
+                          setNativeVoicePrefs(
+                            updateNativeVoicePreferences({
+                              gateMode: e.target.value as "level" | "vad",
+                            })
+                          )
+                        }
+                      >
+                        <option value="level">Volume threshold</option>
+                        <option value="vad">Voice activity</option>
+                      </select>
+                      <label className="profile-label" style={{ marginTop: 8 }}>
+                        Hold time ({nativeVoicePrefs.gateHoldMs} ms)
+                      </label>
+                      <input
+                        type="range"
+                        min={50}
+                        max={1000}
+                        step={10}
+                        value={nativeVoicePrefs.gateHoldMs}
+                        onChange={(e) =>
+                          setNativeVoicePrefs(
+                            updateNativeVoicePreferences({
+                              gateHoldMs: Number(e.target.value),
+                            })
+                          )
+                        }
+                        className="voice-mix-slider"
+                      />
+                      <label className="profile-label" style={{ marginTop: 8 }}>
+                        Release ({nativeVoicePrefs.gateReleaseMs} ms)
+                      </label>
+                      <input
+                        type="range"
+                        min={10}
+                        max={500}
+                        step={10}
+                        value={nativeVoicePrefs.gateReleaseMs}
+                        onChange={(e) =>
+                          setNativeVoicePrefs(
+                            updateNativeVoicePreferences({
+                              gateReleaseMs: Number(e.target.value),
+                            })
+                          )
+                        }
+                        className="voice-mix-slider"
+                      />
+                      <label className="profile-label" style={{ marginTop: 8 }}>
+                        Background level when closed (
+                        {nativeVoicePrefs.gateFloorDb <= -90
+                          ? "silent"
+                          : `${nativeVoicePrefs.gateFloorDb} dB`}
+                        )
+                      </label>
+                      <input
+                        type="range"
+                        min={-90}
+                        max={-10}
+                        step={5}
+                        value={nativeVoicePrefs.gateFloorDb}
+                        onChange={(e) =>
+                          setNativeVoicePrefs(
+                            updateNativeVoicePreferences({
+                              gateFloorDb: Number(e.target.value),
+                            })
+                          )
+                        }
+                        className="voice-mix-slider"
+                      />
+                      <div className="profile-help">
+                        Voice activity only opens the mic for speech, so typing and fans stay out. Hold time keeps the mic open after you stop talking, release fades it out instead of cutting off word endings.
+                      </div>
+                      <label
+                        className="profile-label"
+                        style={{ marginTop: 12, display: "flex", alignItems: "center", gap: 8 }}
+                      >
+                        <input
+                          type="checkbox"
+                          checked={nativeVoicePrefs.suppressTyping}
+                          onChange={(e) =>
+                            setNativeVoicePrefs(
+                              updateNativeVoicePreferences({
+                                suppressTyping: e.target.checked,
+                              })
+                            )
+                          }
+                        />
+                        Suppress typing
+                      </label>
+                      <div className="profile-help">
+                        Closes the mic while you type so keyboard clicks are not sent.
+                      </div>
+                      <label
+                        className="profile-label"
+                        style={{ marginTop: 12, display: "flex", alignItems: "center", gap: 8 }}
+                      >
+                        <input
+                          type="checkbox"
+                          checked={nativeVoicePrefs.autoGainControl}
+                          onChange={(e) =>
+                            setNativeVoicePrefs(
+                              updateNativeVoicePreferences({
+                                autoGainControl: e.target.checked,
+                              })
+                            )
+                          }
+                        />
+                        Automatic gain control
+                      </label>
+                      {nativeVoicePrefs.autoGainControl && (
+                        <>
+                          <label className="profile-label" style={{ marginTop: 8 }}>
+                            Target level ({nativeVoicePrefs.agcTargetDbfs} dBFS)
+                          </label>
+                          <input
+                            type="range"
+                            min={-30}
+                            max={-3}
+                            step={1}
+                            value={nativeVoicePrefs.agcTargetDbfs}
+                            onChange={(e) =>
+                              setNativeVoicePrefs(
+                                updateNativeVoicePreferences({
+                                  agcTargetDbfs: Number(e.target.value),
+                                })
+                              )
+                            }
+                            className="voice-mix-slider"
+                          />
+                          <label className="profile-label" style={{ marginTop: 8 }}>
+                            Maximum gain ({nativeVoicePrefs.agcMaxGainDb} dB)
+                          </label>
+                          <input
+                            type="range"
+                            min={0}
+                            max={50}
+                            step={1}
+                            value={nativeVoicePrefs.agcMaxGainDb}
+                            onChange={(e) =>
+                              setNativeVoicePrefs(
+                                updateNativeVoicePreferences({
+                                  agcMaxGainDb: Number(e.target.value),
+                                })
+                              )
+                            }
+                            className="voice-mix-slider"
+                          />
+                        </>
+                      )}
+                    </div>
+                  )}
                   <div style={{ marginTop: 12 }}>
                     <label className="profile-label">Video background</label>
                     <select
@@ -1312,6 +1809,495 @@ export default function Settings({
                       />
                     </div>
                   </div>
+                  {isProbablyTauri() && (
+                    <div style={{ marginTop: 12 }}>
+                      <label className="profile-label">Hear yourself</label>
+                      <div className="profile-device-row">
+                        <select
+                          className="profile-select"
+                          value={nativeTestOutputId}
+                          onChange={(e) => setNativeTestOutputId(e.target.value)}
+                          disabled={nativeTestRunning}
+                        >
+                          <option value="">Default output</option>
+                          {nativeOutputs.map((device) => (
+                            <option key={device.id} value={device.id}>
+                              {device.label}
+                            </option>
+                          ))}
+                        </select>
+                        <label className="text-xs text-[var(--text-muted)]">
+                          <input
+                            type="checkbox"
+                            checked={nativeTestRecord}
+                            onChange={(e) => setNativeTestRecord(e.target.checked)}
+                            disabled={nativeTestRunning}
+                          />{" "}
+                          Record 5s, then play back
+                        </label>
+                        {nativeTestRunning ? (
+                          <button
+                            type="button"
+                            className="profile-button secondary"
+                            onClick={() => void stopNativeTest()}
+                          >
+                            Stop
+                          </button>
+                        ) : (
+                          <button
+                            type="button"
+                            className="profile-button secondary"
+                            onClick={() => void startNativeTest()}
+                          >
+                            Start
+                          </button>
+                        )}
+                      </div>
+                      <div className="profile-help">
+                        {nativeTestState?.phase === "monitoring" && "Listening live. Use headphones to avoid feedback."}
+                        {nativeTestState?.phase === "recording" && "Recording..."}
+                        {nativeTestState?.phase === "playing" && "Playing back..."}
+                        {nativeTestState?.phase === "failed" && (nativeTestState.error || "Mic test failed.")}
+                        {(!nativeTestState || nativeTestState.phase === "finished") &&
+                          "Runs your default microphone through the desktop voice processing with the settings above, without joining a channel."}
+                      </div>
+                    </div>
+                  )}
+                  {isProbablyTauri() && (
+                    <div style={{ marginTop: 12 }}>
+                      <label className="profile-label">Voice effects</label>
+                      <label
+                        className="profile-label"
+                        style={{ marginTop: 8, display: "flex", alignItems: "center", gap: 8 }}
+                      >
+                        <input
+                          type="checkbox"
+                          checked={nativeVoicePrefs.dynamics.eq.lowCutEnabled}
+                          onChange={(e) => updateDynamics("eq", { lowCutEnabled: e.target.checked })}
+                        />
+                        Low cut ({nativeVoicePrefs.dynamics.eq.lowCutHz} Hz)
+                      </label>
+                      {nativeVoicePrefs.dynamics.eq.lowCutEnabled && (
+                        <input
+                          type="range"
+                          min={40}
+                          max={300}
+                          step={10}
+                          value={nativeVoicePrefs.dynamics.eq.lowCutHz}
+                          onChange={(e) => updateDynamics("eq", { lowCutHz: Number(e.target.value) })}
+                          className="voice-mix-slider"
+                        />
+                      )}
+                      <label
+                        className="profile-label"
+                        style={{ marginTop: 8, display: "flex", alignItems: "center", gap: 8 }}
+                      >
+                        <input
+                          type="checkbox"
+                          checked={nativeVoicePrefs.dynamics.eq.presenceEnabled}
+                          onChange={(e) =>
+                            updateDynamics("eq", { presenceEnabled: e.target.checked })
+                          }
+                        />
+                        Presence boost ({nativeVoicePrefs.dynamics.eq.presenceGainDb} dB at{" "}
+                        {nativeVoicePrefs.dynamics.eq.presenceHz} Hz)
+                      </label>
+                      {nativeVoicePrefs.dynamics.eq.presenceEnabled && (
+                        <>
+                          <input
+                            type="range"
+                            min={-6}
+                            max={12}
+                            step={1}
+                            value={nativeVoicePrefs.dynamics.eq.presenceGainDb}
+                            onChange={(e) =>
+                              updateDynamics("eq", { presenceGainDb: Number(e.target.value) })
+                            }
+                            className="voice-mix-slider"
+                          />
+                          <input
+                            type="range"
+                            min={1500}
+                            max={8000}
+                            step={250}
+                            value={nativeVoicePrefs.dynamics.eq.presenceHz}
+                            onChange={(e) =>
+                              updateDynamics("eq", { presenceHz: Number(e.target.value) })
+                            }
+                            className="voice-mix-slider"
+                          />
+                        </>
+                      )}
+                      <label
+                        className="profile-label"
+                        style={{ marginTop: 8, display: "flex", alignItems: "center", gap: 8 }}
+                      >
+                        <input
+                          type="checkbox"
+                          checked={nativeVoicePrefs.dynamics.compressor.enabled}
+                          onChange={(e) => updateDynamics("compressor", { enabled: e.target.checked })}
+                        />
+                        Compressor
+                      </label>
+                      {nativeVoicePrefs.dynamics.compressor.enabled && (
+                        <>
+                          <label className="profile-label" style={{ marginTop: 8 }}>
+                            Threshold ({nativeVoicePrefs.dynamics.compressor.thresholdDb} dB)
+                          </label>
+                          <input
+                            type="range"
+                            min={-50}
+                            max={0}
+                            step={1}
+                            value={nativeVoicePrefs.dynamics.compressor.thresholdDb}
+                            onChange={(e) =>
+                              updateDynamics("compressor", { thresholdDb: Number(e.target.value) })
+                            }
+                            className="voice-mix-slider"
+                          />
+                          <label className="profile-label" style={{ marginTop: 8 }}>
+                            Ratio ({nativeVoicePrefs.dynamics.compressor.ratio}:1)
+                          </label>
+                          <input
+                            type="range"
+                            min={1}
+                            max={20}
+                            step={0.5}
+                            value={nativeVoicePrefs.dynamics.compressor.ratio}
+                            onChange={(e) =>
+                              updateDynamics("compressor", { ratio: Number(e.target.value) })
+                            }
+                            className="voice-mix-slider"
+                          />
+                          <label className="profile-label" style={{ marginTop: 8 }}>
+                            Attack ({nativeVoicePrefs.dynamics.compressor.attackMs} ms)
+                          </label>
+                          <input
+                            type="range"
+                            min={1}
+                            max={100}
+                            step={1}
+                            value={nativeVoicePrefs.dynamics.compressor.attackMs}
+                            onChange={(e) =>
+                              updateDynamics("compressor", { attackMs: Number(e.target.value) })
+                            }
+                            className="voice-mix-slider"
+                          />
+                          <label className="profile-label" style={{ marginTop: 8 }}>
+                            Release ({nativeVoicePrefs.dynamics.compressor.releaseMs} ms)
+                          </label>
+                          <input
+                            type="range"
+                            min={20}
+                            max={1000}
+                            step={10}
+                            value={nativeVoicePrefs.dynamics.compressor.releaseMs}
+                            onChange={(e) =>
+                              updateDynamics("compressor", { releaseMs: Number(e.target.value) })
+                            }
+                            className="voice-mix-slider"
+                          />
+                          <label className="profile-label" style={{ marginTop: 8 }}>
+                            Makeup gain ({nativeVoicePrefs.dynamics.compressor.makeupDb} dB)
+                          </label>
+                          <input
+                            type="range"
+                            min={0}
+                            max={24}
+                            step={1}
+                            value={nativeVoicePrefs.dynamics.compressor.makeupDb}
+                            onChange={(e) =>
+                              updateDynamics("compressor", { makeupDb: Number(e.target.value) })
+                            }
+                            className="voice-mix-slider"
+                          />
+                        </>
+                      )}
+                      <label
+                        className="profile-label"
+                        style={{ marginTop: 8, display: "flex", alignItems: "center", gap: 8 }}
+                      >
+                        <input
+                          type="checkbox"
+                          checked={nativeVoicePrefs.dynamics.limiter.enabled}
+                          onChange={(e) => updateDynamics("limiter", { enabled: e.target.checked })}
+                        />
+                        Limiter ({nativeVoicePrefs.dynamics.limiter.ceilingDb} dB ceiling)
+                      </label>
+                      {nativeVoicePrefs.dynamics.limiter.enabled && (
+                        <input
+                          type="range"
+                          min={-12}
+                          max={0}
+                          step={0.5}
+                          value={nativeVoicePrefs.dynamics.limiter.ceilingDb}
+                          onChange={(e) =>
+                            updateDynamics("limiter", { ceilingDb: Number(e.target.value) })
+                          }
+                          className="voice-mix-slider"
+                        />
+                      )}
+                      <div className="profile-device-row" style={{ marginTop: 8 }}>
+                        <select
+                          className="profile-select"
+                          value=""
+                          onChange={(e) => {
+                            const preset = nativeVoicePrefs.dynamicsPresets.find(
+                              (item) => item.id === e.target.value
+                            );
+                            if (!preset) return;
+                            setNativeVoicePrefs(
+                              updateNativeVoicePreferences({ dynamics: preset.settings })
+                            );
+                            setDynamicsPresetName(preset.name);
+                          }}
+                        >
+                          <option value="">Load preset...</option>
+                          {nativeVoicePrefs.dynamicsPresets.map((preset) => (
+                            <option key={preset.id} value={preset.id}>
+                              {preset.name}
+                            </option>
+                          ))}
+                        </select>
+                        <input
+                          className="profile-input"
+                          placeholder="Preset name"
+                          value={dynamicsPresetName}
+                          onChange={(e) => setDynamicsPresetName(e.target.value)}
+                        />
+                        <button
+                          type="button"
+                          className="profile-button secondary"
+                          disabled={!dynamicsPresetName.trim()}
+                          onClick={saveDynamicsPreset}
+                        >
+                          Save
+                        </button>
+                        {nativeVoicePrefs.dynamicsPresets.some(
+                          (preset) => preset.name === dynamicsPresetName.trim()
+                        ) && (
+                          <button
+                            type="button"
+                            className="profile-button secondary"
+                            onClick={() => {
+                              setNativeVoicePrefs(
+                                updateNativeVoicePreferences({
+                                  dynamicsPresets: nativeVoicePrefs.dynamicsPresets.filter(
+                                    (preset) => preset.name !== dynamicsPresetName.trim()
+                                  ),
+                                })
+                              );
+                              setDynamicsPresetName("");
+                            }}
+                          >
+                            Delete
+                          </button>
+                        )}
+                      </div>
+                      <div className="profile-help">
+                        Applied after noise suppression and the gate, and updated live while you talk. The low cut removes rumble, the presence boost adds clarity, the compressor evens out your level and the limiter stops loud moments from clipping.
+                      </div>
+                    </div>
+                  )}
+                  {isProbablyTauri() && (
+                    <div style={{ marginTop: 12 }}>
+                      <label className="profile-label">Soundboard</label>
+                      {nativeVoicePrefs.soundboardClips.map((clip) => (
+                        <div key={clip.id} className="profile-device-row">
+                          <span className="text-xs" style={{ flex: 1 }} title={clip.path}>
+                            {clip.name}
+                          </span>
+                          <input
+                            type="range"
+                            min={0}
+                            max={200}
+                            step={5}
+                            value={Math.round(clip.volume * 100)}
+                            title={`Volume ${Math.round(clip.volume * 100)}%`}
+                            onChange={(e) =>
+                              setNativeVoicePrefs(
+                                updateNativeVoicePreferences({
+                                  soundboardClips: nativeVoicePrefs.soundboardClips.map((item) =>
+                                    item.id === clip.id
+                                      ? { ...item, volume: Number(e.target.value) / 100 }
+                                      : item
+                                  ),
+                                })
+                              )
+                            }
+                          />
+                          <button
+                            type="button"
+                            className="profile-button secondary"
+                            onClick={() =>
+                              setNativeVoicePrefs(
+                                updateNativeVoicePreferences({
+                                  soundboardClips: nativeVoicePrefs.soundboardClips.filter(
+                                    (item) => item.id !== clip.id
+                                  ),
+                                })
+                              )
+                            }
+                          >
+                            Remove
+                          </button>
+                        </div>
+                      ))}
+                      <button
+                        type="button"
+                        className="profile-button secondary"
+                        style={{ marginTop: 6 }}
+                        onClick={() => void addSoundboardClip()}
+                      >
+                        Add clip
+                      </button>
+                      <div className="profile-help">
+                        WAV or Ogg files up to a minute long. Clips are mixed into your microphone after noise suppression and play even while the gate is closed.
+                      </div>
+                    </div>
+                  )}
+                  {isProbablyTauri() && canExcludeOwnShareAudio && (
+                    <div style={{ marginTop: 12 }}>
+                      <label
+                        className="profile-label"
+                        style={{ display: "flex", alignItems: "center", gap: 8 }}
+                      >
+                        <input
+                          type="checkbox"
+                          checked={nativeVoicePrefs.excludeOwnShareAudio}
+                          onChange={(e) =>
+                            setNativeVoicePrefs(
+                              updateNativeVoicePreferences({
+                                excludeOwnShareAudio: e.target.checked,
+                              })
+                            )
+                          }
+                        />
+                        Leave voice chat out of shared audio
+                      </label>
+                      <div className="profile-help">
+                        Viewers of your screen share hear your computer's sound without the voice channel playing back a second time. Needs Windows 10 version 2004 or later; older versions share everything.
+                      </div>
+                    </div>
+                  )}
+                  {isProbablyTauri() && (
+                    <div style={{ marginTop: 12 }}>
+                      <label
+                        className="profile-label"
+                        style={{ display: "flex", alignItems: "center", gap: 8 }}
+                      >
+                        <input
+                          type="checkbox"
+                          checked={nativeVoicePrefs.nativePlayback}
+                          onChange={(e) =>
+                            setNativeVoicePrefs(
+                              updateNativeVoicePreferences({
+                                nativePlayback: e.target.checked,
+                              })
+                            )
+                          }
+                        />
+                        Play voice chat through the native audio engine
+                      </label>
+                      <div className="profile-help">
+                        Other participants play through the app's own audio output instead of the browser, which lets you pan them left or right and gives echo cancellation an exact copy of what your speakers play. Pick the output device from the voice panel while in a call.
+                      </div>
+                    </div>
+                  )}
+                  {isProbablyTauri() && (
+                    <div style={{ marginTop: 12 }}>
+                      <label
+                        className="profile-label"
+                        style={{ display: "flex", alignItems: "center", gap: 8 }}
+                      >
+                        <input
+                          type="checkbox"
+                          checked={nativeVoicePrefs.ducking.enabled}
+                          onChange={(e) => updateDucking({ enabled: e.target.checked })}
+                        />
+                        Lower shared audio while people talk
+                      </label>
+                      {nativeVoicePrefs.ducking.enabled && (
+                        <>
+                          <label className="profile-label" style={{ marginTop: 8 }}>
+                            Depth ({nativeVoicePrefs.ducking.depthDb} dB)
+                          </label>
+                          <input
+                            type="range"
+                            min={3}
+                            max={40}
+                            step={1}
+                            value={nativeVoicePrefs.ducking.depthDb}
+                            onChange={(e) => updateDucking({ depthDb: Number(e.target.value) })}
+                            className="voice-mix-slider"
+                          />
+                          <label className="profile-label" style={{ marginTop: 8 }}>
+                            Voice threshold ({nativeVoicePrefs.ducking.thresholdDb} dB)
+                          </label>
+                          <input
+                            type="range"
+                            min={-70}
+                            max={-20}
+                            step={1}
+                            value={nativeVoicePrefs.ducking.thresholdDb}
+                            onChange={(e) => updateDucking({ thresholdDb: Number(e.target.value) })}
+                            className="voice-mix-slider"
+                          />
+                          <label className="profile-label" style={{ marginTop: 8 }}>
+                            Attack ({nativeVoicePrefs.ducking.attackMs} ms)
+                          </label>
+                          <input
+                            type="range"
+                            min={5}
+                            max={500}
+                            step={5}
+                            value={nativeVoicePrefs.ducking.attackMs}
+                            onChange={(e) => updateDucking({ attackMs: Number(e.target.value) })}
+                            className="voice-mix-slider"
+                          />
+                          <label className="profile-label" style={{ marginTop: 8 }}>
+                            Hold ({nativeVoicePrefs.ducking.holdMs} ms)
+                          </label>
+                          <input
+                            type="range"
+                            min={0}
+                            max={2000}
+                            step={50}
+                            value={nativeVoicePrefs.ducking.holdMs}
+                            onChange={(e) => updateDucking({ holdMs: Number(e.target.value) })}
+                            className="voice-mix-slider"
+                          />
+                          <label className="profile-label" style={{ marginTop: 8 }}>
+                            Release ({nativeVoicePrefs.ducking.releaseMs} ms)
+                          </label>
+                          <input
+                            type="range"
+                            min={50}
+                            max={3000}
+                            step={50}
+                            value={nativeVoicePrefs.ducking.releaseMs}
+                            onChange={(e) => updateDucking({ releaseMs: Number(e.target.value) })}
+                            className="voice-mix-slider"
+                          />
+                          <label
+                            className="profile-label"
+                            style={{ marginTop: 8, display: "flex", alignItems: "center", gap: 8 }}
+                          >
+                            <input
+                              type="checkbox"
+                              checked={nativeVoicePrefs.ducking.duckPlayback}
+                              disabled={!nativeVoicePrefs.nativePlayback}
+                              onChange={(e) => updateDucking({ duckPlayback: e.target.checked })}
+                            />
+                            Also lower other people's shared audio for me
+                          </label>
+                        </>
+                      )}
+                      <div className="profile-help">
+                        Turns your screen share's sound down while your microphone is open or someone else is talking, so viewers can still follow the conversation. The threshold sets how loud another participant has to be to count as talking. Lowering other people's shared audio needs native playback.
+                      </div>
+                    </div>
+                  )}
                   <p className="text-xs text-[var(--text-muted)]" style={{ marginTop: 8 }}>
                     Screen share quality is chosen when you start sharing.
                   </p>
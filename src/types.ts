@@ -53,6 +53,8 @@ export interface VoiceControls {
   isDeafened: boolean;
   isCameraOn: boolean;
   isScreenSharing: boolean;
+  /** What a native screen share's audio carries; null without audio or for browser shares */
+  screenShareAudioMode?: "application" | "system-excluding-self" | "system" | null;
   isNoiseSuppressionEnabled: boolean;
   usesNativeAudioInput?: boolean;
   toggleMute: () => void;
@@ -72,6 +74,8 @@ export interface VoiceControls {
   listScreenShareSources?: () => Promise<ScreenShareSource[]>;
   /** Enumerate native microphone devices when available */
   listAudioInputDevices?: () => Promise<AudioInputDeviceOption[]>;
+  /** Enumerate native output devices when remote audio plays natively */
+  listAudioOutputDevices?: () => Promise<AudioInputDeviceOption[]>;
   /** Switch active microphone device (empty = default) */
   setAudioInputDevice: (deviceId: string) => Promise<void>;
   /** Switch active speaker device (empty = default) */
@@ -86,10 +90,23 @@ export interface VoiceControls {
     maxScreenShareResolution: string;
     maxScreenShareFps: number;
   };
+  /** Whether the native client is recording this voice session */
+  isRecording?: boolean;
+  /** Start or stop recording the session; only available with the native microphone */
+  toggleRecording?: () => Promise<void>;
+  /** Soundboard clips that can be played into the native microphone */
+  soundboardClips?: { id: string; name: string }[];
+  /** Clip ids currently playing */
+  playingSoundboardClips?: string[];
+  playSoundboardClip?: (clipId: string) => Promise<void>;
+  stopSoundboard?: () => Promise<void>;
   /** Per-participant local volume (0–1). Keyed by participant identity. */
   participantVolumes: Record<string, number>;
   /** Set local playback volume for a participant (0–1). */
   setParticipantVolume: (participantId: string, volume: number) => void;
+  /** Per-participant stereo pan (-1 left to 1 right); only with native playback. */
+  participantPans?: Record<string, number>;
+  setParticipantPan?: (participantId: string, pan: number) => void;
 }
 
 export interface ScreenShareSource {